@@ -1,4 +1,4 @@
-use bitvec::*;
+use bitvec::prelude::*;
 
 pub trait BCHBitVec {
     fn finite_add(&self, vec: &BitVec) -> BitVec;
@@ -6,6 +6,7 @@ pub trait BCHBitVec {
     fn inverse_nth(&mut self, n: usize); //TODO not safe
     fn precede_with_zeros(&mut self, n: usize);
     fn remainder_divide(&self, divisor_orig: &BitVec) -> Result<BitVec, String>;
+    fn quotient_divide(&self, divisor_orig: &BitVec) -> Result<BitVec, String>;
     fn shift_cyclic(&mut self, n: i32); //TODO to doc: negatives shift rights, positives shift left
 }
 
@@ -14,32 +15,31 @@ impl BCHBitVec for BitVec {
         let mut self_clone = self.clone();
         let mut vec_clone = vec.clone();
         if self_clone.len() > vec.len() {
-            vec_clone >>= self_clone.len() - vec_clone.len();
+            vec_clone.precede_with_zeros(self_clone.len() - vec_clone.len());
         } else if vec.len() > self_clone.len() {
-            self_clone >>= vec_clone.len() - self_clone.len();
+            self_clone.precede_with_zeros(vec_clone.len() - self_clone.len());
         }
 
         self_clone ^ vec_clone
     }
 
     fn inverse_nth(&mut self, n: usize) {
-        if self.get(n) == false {
-            self.set(n, true);
-        } else {
-            self.set(n, false);
-        }
+        let bit = self[n];
+        self.set(n, !bit);
     }
 
     fn truncate_preceding_zeros(&mut self) {
-        let pos = self.iter().position(|bit| bit == true);
+        let pos = self.iter().by_vals().position(|bit| bit);
         match pos {
-            Some(i) => *self = self.iter().skip(i).collect(),
+            Some(i) => *self = self[i..].to_bitvec(),
             None => *self = bitvec![],
         }
     }
 
     fn precede_with_zeros(&mut self, n: usize) {
-        *self >>= n;
+        let mut preceded = bitvec![0; n];
+        preceded.extend_from_bitslice(self);
+        *self = preceded;
     }
 
     fn remainder_divide(&self, divisor_orig: &BitVec) -> Result<BitVec, String> {
@@ -60,17 +60,46 @@ impl BCHBitVec for BitVec {
         let mut remainder: BitVec = self_clone.iter().take(divisor.len()).collect();
         remainder ^= divisor.clone();
 
-        for bit in self_clone.iter().skip(divisor.len()) {
-            remainder <<= 1;
+        for bit in self_clone.iter().by_vals().skip(divisor.len()) {
+            remainder.remove(0);
             remainder.push(bit);
-            if remainder.get(0) == true {
+            if remainder[0] {
                 remainder ^= divisor.clone();
             }
         }
-        remainder <<= 1;
+        remainder.remove(0);
         Ok(remainder)
     }
 
+    fn quotient_divide(&self, divisor_orig: &BitVec) -> Result<BitVec, String> {
+        let mut remainder = self.clone();
+        let mut divisor = divisor_orig.clone();
+
+        remainder.truncate_preceding_zeros();
+        divisor.truncate_preceding_zeros();
+
+        if divisor.len() == 0 {
+            return Err("Division by zero polynomial!".to_owned());
+        }
+        if divisor.len() > remainder.len() {
+            return Ok(bitvec![]);
+        }
+
+        let quotient_len = remainder.len() - divisor.len() + 1;
+        let mut quotient = bitvec![0; quotient_len];
+        for i in 0..quotient_len {
+            if remainder[i] {
+                quotient.set(i, true);
+                for (j, bit) in divisor.iter().by_vals().enumerate() {
+                    if bit {
+                        remainder.inverse_nth(i + j);
+                    }
+                }
+            }
+        }
+        Ok(quotient)
+    }
+
     fn shift_cyclic(&mut self, n: i32) {
         let tmp_clone = self.clone();
         let len = tmp_clone.len() as i32;
@@ -79,7 +108,7 @@ impl BCHBitVec for BitVec {
             if pos < 0 {
                 pos += len;
             }
-            let bit = tmp_clone.get(pos as usize);
+            let bit = tmp_clone[pos as usize];
             self.set(i as usize, bit);
         }
     }
@@ -174,6 +203,20 @@ mod tests {
         assert_eq!(expected, result);
     }
 
+    #[test]
+    fn quotient_divide_test() {
+        // x^7 + 1 = (x^3 + x + 1)(x^4 + x^2 + x + 1)
+        let vec = bitvec![1, 0, 0, 0, 0, 0, 0, 1];
+        let divisor = bitvec![1, 0, 1, 1];
+        let expected = bitvec![1, 0, 1, 1, 1];
+        assert_eq!(vec.quotient_divide(&divisor).unwrap(), expected);
+
+        let vec = bitvec![0, 1, 1, 0, 1];
+        assert_eq!(vec.quotient_divide(&divisor).unwrap(), bitvec![1]);
+        assert_eq!(bitvec![1, 1].quotient_divide(&divisor).unwrap(), bitvec![]);
+        assert!(vec.quotient_divide(&bitvec![0, 0]).is_err());
+    }
+
     #[test]
     fn truncate_preceding_zeros() {
         let mut vec = bitvec![0, 0, 1, 1];
@@ -0,0 +1,132 @@
+use crate::common;
+use crate::galois_field::GaloisField;
+use bitvec::prelude::*;
+
+/// Parameters of a binary BCH code over GF(2^m), m being the degree of the
+/// primitive polynomial. Roots of the generator polynomial are 2t consecutive
+/// powers of beta = alpha^((2^m - 1) / cycle_len) starting at beta^first_root.
+/// For primitive codes cycle_len is 2^m - 1, codes with n < cycle_len are
+/// shortened. Narrow-sense codes start at the first power.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BchCode {
+    pub n: i32,
+    pub k: i32,
+    pub t: i32,
+    pub prime_poly: BitVec,
+    pub cycle_len: i32,
+    pub first_root: i32,
+}
+
+impl BchCode {
+    pub fn new(n: i32, k: i32, t: i32, prime_poly: &BitVec) -> BchCode {
+        BchCode {
+            n: n,
+            k: k,
+            t: t,
+            prime_poly: prime_poly.clone(),
+            cycle_len: 2i32.pow(prime_poly.len() as u32 - 1) - 1,
+            first_root: 1,
+        }
+    }
+
+    /// Non-primitive code, n has to divide 2^m - 1.
+    pub fn new_non_primitive(n: i32, k: i32, t: i32, prime_poly: &BitVec) -> BchCode {
+        let code = BchCode {
+            n: n,
+            k: k,
+            t: t,
+            prime_poly: prime_poly.clone(),
+            cycle_len: n,
+            first_root: 1,
+        };
+        if n <= 0 || code.field_order() % n != 0 {
+            panic!(
+                "Bad code parameters. n: {} does not divide {}",
+                n,
+                code.field_order()
+            );
+        }
+        code
+    }
+
+    /// Same code with consecutive roots starting at beta^first_root.
+    pub fn with_first_root(mut self, first_root: i32) -> BchCode {
+        if first_root < 0 {
+            panic!("Bad code parameters. first root: {}", first_root);
+        }
+        self.first_root = first_root;
+        self
+    }
+
+    /// Same code with k = n - deg g, not positive when the generator leaves
+    /// no message bits.
+    pub fn with_derived_k(mut self) -> BchCode {
+        self.k = self.n - (self.gen_poly().len() as i32 - 1);
+        self
+    }
+
+    pub fn m(&self) -> i32 {
+        self.prime_poly.len() as i32 - 1
+    }
+
+    /// Number of non zero elements of GF(2^m).
+    pub fn field_order(&self) -> i32 {
+        2i32.pow(self.m() as u32) - 1
+    }
+
+    pub fn gen_poly(&self) -> BitVec {
+        self.gen_poly_and_field().0
+    }
+
+    pub fn gen_poly_and_field(&self) -> (BitVec, GaloisField) {
+        common::get_cyclic_gen_poly_and_field(
+            self.cycle_len,
+            self.t,
+            self.first_root,
+            &self.prime_poly,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn primitive_code_test() {
+        let code = BchCode::new(31, 16, 3, &bitvec![1, 0, 0, 1, 0, 1]);
+        assert_eq!(code.m(), 5);
+        assert_eq!(code.field_order(), 31);
+        assert_eq!(code.cycle_len, 31);
+        assert_eq!(code.gen_poly().len() as i32 - 1, code.n - code.k);
+    }
+
+    #[test]
+    fn non_primitive_code_test() {
+        let code = BchCode::new_non_primitive(21, 12, 2, &bitvec![1, 0, 0, 0, 0, 1, 1]);
+        assert_eq!(code.field_order(), 63);
+        assert_eq!(code.cycle_len, 21);
+        assert_eq!(code.gen_poly().len() as i32 - 1, code.n - code.k);
+    }
+
+    #[test]
+    fn non_narrow_sense_code_test() {
+        let code = BchCode::new(15, 6, 2, &bitvec![1, 0, 0, 1, 1]).with_first_root(0);
+        assert_eq!(code.first_root, 0);
+        assert_eq!(code.gen_poly().len() as i32 - 1, code.n - code.k);
+    }
+
+    #[test]
+    fn derived_k_test() {
+        let code = BchCode::new(31, 0, 3, &bitvec![1, 0, 0, 1, 0, 1]).with_derived_k();
+        assert_eq!(code.k, 16);
+        let code = BchCode::new(15, 0, 8, &bitvec![1, 0, 0, 1, 1]).with_derived_k();
+        assert!(code.k <= 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn non_primitive_code_fail_when_n_does_not_divide_field_order() {
+        BchCode::new_non_primitive(20, 11, 2, &bitvec![1, 0, 0, 0, 0, 1, 1]);
+    }
+}
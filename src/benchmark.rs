@@ -0,0 +1,283 @@
+use crate::bch_code::BchCode;
+use crate::berlekamp_decoder::BerlekampDecoder;
+use crate::channel::{Channel, FixedErrorsChannel};
+use crate::common;
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::rng;
+use crate::simple_decoder::SimpleDecoder;
+use bitvec::prelude::*;
+use std::time::{Duration, Instant};
+
+/// Words prepared up front and cycled through while timing.
+const WORD_POOL: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operation {
+    Encode,
+    SimpleDecode,
+    BerlekampDecode,
+}
+
+impl Operation {
+    pub fn all() -> Vec<Operation> {
+        vec![
+            Operation::Encode,
+            Operation::SimpleDecode,
+            Operation::BerlekampDecode,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::Encode => "encode",
+            Operation::SimpleDecode => "simple",
+            Operation::BerlekampDecode => "berlekamp",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Operation, String> {
+        Operation::all()
+            .into_iter()
+            .find(|operation| operation.name() == name)
+            .ok_or_else(|| format!("Unknown operation: {}", name))
+    }
+}
+
+/// Every measurement runs at least min_words words and at least min_time.
+#[derive(Clone, Debug)]
+pub struct BenchmarkConfig {
+    pub min_time: Duration,
+    pub min_words: u64,
+    pub seed: u64,
+}
+
+impl BenchmarkConfig {
+    pub fn new(min_time: Duration, min_words: u64, seed: u64) -> BenchmarkConfig {
+        BenchmarkConfig {
+            min_time: min_time,
+            min_words: min_words,
+            seed: seed,
+        }
+    }
+}
+
+/// Failures are words the decoder rejected or decoded to a wrong message.
+#[derive(Clone, Debug)]
+pub struct BenchmarkResult {
+    pub operation: Operation,
+    pub n: i32,
+    pub k: i32,
+    pub t: i32,
+    pub errors: usize,
+    pub words: u64,
+    pub failures: u64,
+    pub elapsed: Duration,
+}
+
+impl BenchmarkResult {
+    /// Message bits processed per second, in millions.
+    pub fn mbit_per_s(&self) -> f64 {
+        let seconds = self.elapsed.as_secs() as f64 + self.elapsed.subsec_nanos() as f64 * 1e-9;
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        (self.words * self.k as u64) as f64 / seconds / 1e6
+    }
+}
+
+/// Codes of the default suite, the primitive code correcting m errors for
+/// every m from 4 to 14.
+pub fn default_codes() -> Vec<BchCode> {
+    (4..15)
+        .map(|m| {
+            let n = 2i32.pow(m) - 1;
+            BchCode::new(n, 0, m as i32, &common::get_primitive_poly(m).unwrap()).with_derived_k()
+        })
+        .collect()
+}
+
+/// Longest code of the default suite on which the SimpleDecoder runs for
+/// the whole minimum time. Its cost grows with n^2, a single word of the
+/// longest codes takes minutes.
+pub const SIMPLE_DECODE_MAX_N: i32 = 1023;
+
+/// Configuration of the default suite for the operation on the code, the
+/// SimpleDecoder times a single word per point on codes longer than
+/// SIMPLE_DECODE_MAX_N.
+pub fn default_config(
+    code: &BchCode,
+    operation: Operation,
+    config: &BenchmarkConfig,
+) -> BenchmarkConfig {
+    if operation == Operation::SimpleDecode && code.n > SIMPLE_DECODE_MAX_N {
+        BenchmarkConfig::new(Duration::from_secs(0), 1, config.seed)
+    } else {
+        config.clone()
+    }
+}
+
+/// Error counts measured for decoders, 0, t/2 and t.
+pub fn error_counts(t: i32) -> Vec<usize> {
+    let mut counts = vec![0, t as usize / 2, t as usize];
+    counts.dedup();
+    counts
+}
+
+/// Measures the operations on one code. Encoding is timed once, decoding at
+/// every count of error_counts.
+pub fn benchmark_code(
+    code: &BchCode,
+    operations: &[Operation],
+    config: &BenchmarkConfig,
+) -> Vec<BenchmarkResult> {
+    let encoder = Encoder::from_code(code);
+    let mut results = Vec::new();
+    for operation in operations {
+        match operation {
+            Operation::Encode => {
+                let (msgs, _) = prepare_words(code, &encoder, 0, config.seed);
+                results.push(measure(code, *operation, 0, config, &msgs, |msg| {
+                    encoder.encode(msg).is_ok()
+                }));
+            }
+            Operation::SimpleDecode | Operation::BerlekampDecode => {
+                let decoder: Box<dyn Decoder> = match operation {
+                    Operation::SimpleDecode => Box::new(SimpleDecoder::from_code(code)),
+                    _ => Box::new(BerlekampDecoder::from_code(code)),
+                };
+                for errors in error_counts(code.t) {
+                    let (msgs, words) = prepare_words(code, &encoder, errors, config.seed);
+                    let mut msgs = msgs.iter().cycle();
+                    results.push(measure(code, *operation, errors, config, &words, |word| {
+                        let msg = msgs.next().unwrap();
+                        match decoder.decode(word) {
+                            Ok((decoded, _)) => decoded == *msg,
+                            Err(_) => false,
+                        }
+                    }));
+                }
+            }
+        }
+    }
+    results
+}
+
+/// Random messages and their code words with exactly `errors` flipped bits.
+fn prepare_words(
+    code: &BchCode,
+    encoder: &Encoder,
+    errors: usize,
+    seed: u64,
+) -> (Vec<BitVec>, Vec<BitVec>) {
+    let mut rng = rng::seeded_rng(seed);
+    let mut channel = FixedErrorsChannel::new(errors);
+    (0..WORD_POOL)
+        .map(|_| {
+            let msg = common::get_random_msg(code.k as usize, &mut rng);
+            let word = channel.transmit(&encoder.encode(&msg).unwrap(), &mut rng);
+            (msg, word)
+        })
+        .unzip()
+}
+
+fn measure<F>(
+    code: &BchCode,
+    operation: Operation,
+    errors: usize,
+    config: &BenchmarkConfig,
+    inputs: &[BitVec],
+    mut run: F,
+) -> BenchmarkResult
+where
+    F: FnMut(&BitVec) -> bool,
+{
+    let mut words = 0;
+    let mut failures = 0;
+    let start = Instant::now();
+    while words < config.min_words || start.elapsed() < config.min_time {
+        if !run(&inputs[words as usize % inputs.len()]) {
+            failures += 1;
+        }
+        words += 1;
+    }
+    BenchmarkResult {
+        operation: operation,
+        n: code.n,
+        k: code.k,
+        t: code.t,
+        errors: errors,
+        words: words,
+        failures: failures,
+        elapsed: start.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_codes_test() {
+        let codes = default_codes();
+        assert_eq!(codes.len(), 11);
+        assert_eq!((codes[0].n, codes[0].k, codes[0].t), (15, 1, 4));
+        assert_eq!((codes[1].n, codes[1].k, codes[1].t), (31, 11, 5));
+        assert!(codes.iter().all(|code| code.k > 0));
+        assert_eq!(codes[10].n, 16383);
+    }
+
+    #[test]
+    fn default_config_test() {
+        let codes = default_codes();
+        let config = BenchmarkConfig::new(Duration::from_secs(1), 1, 5);
+        for operation in Operation::all() {
+            let short = default_config(&codes[6], operation, &config);
+            assert_eq!((short.min_time, short.min_words), (config.min_time, 1));
+        }
+        let long = default_config(&codes[7], Operation::SimpleDecode, &config);
+        assert_eq!(
+            (long.min_time, long.min_words, long.seed),
+            (Duration::from_secs(0), 1, 5)
+        );
+        let long = default_config(&codes[7], Operation::BerlekampDecode, &config);
+        assert_eq!(long.min_time, config.min_time);
+    }
+
+    #[test]
+    fn error_counts_test() {
+        assert_eq!(error_counts(3), vec![0, 1, 3]);
+        assert_eq!(error_counts(1), vec![0, 1]);
+    }
+
+    #[test]
+    fn benchmark_code_test() {
+        let code = BchCode::new(31, 16, 3, &bitvec![1, 0, 0, 1, 0, 1]);
+        let config =
+            BenchmarkConfig::new(Duration::from_millis(1), 40, rng::seed_from_env().unwrap());
+        let results = benchmark_code(&code, &Operation::all(), &config);
+
+        // encode, then 0, 1 and 3 errors for both decoders
+        assert_eq!(results.len(), 7);
+        assert_eq!(results[0].operation, Operation::Encode);
+        for result in &results {
+            assert!(result.words >= 40);
+            assert!(result.mbit_per_s() > 0.0);
+        }
+        let berlekamp = results
+            .iter()
+            .filter(|result| result.operation == Operation::BerlekampDecode);
+        for result in berlekamp {
+            assert_eq!(result.failures, 0, "seed: {}", config.seed);
+        }
+    }
+
+    #[test]
+    fn operation_parse_test() {
+        assert_eq!(
+            Operation::parse("berlekamp"),
+            Ok(Operation::BerlekampDecode)
+        );
+        assert!(Operation::parse("fast").is_err());
+    }
+}
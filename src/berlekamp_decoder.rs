@@ -1,46 +1,47 @@
 use crate::bch_bitvec::*;
+use crate::bch_code::BchCode;
 use crate::common;
 use crate::decoder::*;
-use bitvec::*;
+use crate::galois_field::GaloisField;
+use bitvec::prelude::*;
 
 #[derive(Clone)]
 pub struct BerlekampDecoder {
     n: i32,
     k: i32,
     t: i32,
-    gen_poly: BitVec,
-    adding_table: Vec<Vec<i32>>,
+    cycle_len: i32,
+    first_root: i32,
+    field: GaloisField,
 }
 
 impl BerlekampDecoder {
     pub fn new(n: i32, k: i32, t: i32, prime_poly: &BitVec) -> BerlekampDecoder {
-        let prime_degree = prime_poly.len() as i32 - 1;
-        let (gen_poly, adding_table) =
-            common::get_gen_poly_and_adding_table(prime_degree, t, prime_poly);
-        common::validate_params(n, k, &gen_poly, prime_poly);
+        BerlekampDecoder::from_code(&BchCode::new(n, k, t, prime_poly))
+    }
+
+    pub fn from_code(code: &BchCode) -> BerlekampDecoder {
+        let (gen_poly, field) = code.gen_poly_and_field();
+        common::validate_params(code.n, code.k, &gen_poly, &code.prime_poly);
         BerlekampDecoder {
-            n: n,
-            k: k,
-            t: t,
-            gen_poly: gen_poly,
-            adding_table: adding_table,
+            n: code.n,
+            k: code.k,
+            t: code.t,
+            cycle_len: code.cycle_len,
+            first_root: code.first_root,
+            field: field,
         }
     }
 
-    pub fn new_with_gen_poly(
-        n: i32,
-        k: i32,
-        t: i32,
-        gen_poly: BitVec,
-        adding_table: Vec<Vec<i32>>,
-    ) -> BerlekampDecoder {
+    pub fn new_with_field(n: i32, k: i32, t: i32, field: GaloisField) -> BerlekampDecoder {
         //TODO validate or load from file
         BerlekampDecoder {
             n: n,
             k: k,
             t: t,
-            gen_poly: gen_poly,
-            adding_table: adding_table,
+            cycle_len: field.order(),
+            first_root: 1,
+            field: field,
         }
     }
 
@@ -54,258 +55,238 @@ impl BerlekampDecoder {
     }
 
     fn get_first_n_min_pols(&self, n: u32) -> Vec<BitVec> {
-        let mut min_pols = Vec::new();
-        min_pols.resize_default(n as usize);
-        let layers = common::get_n_first_layers(n, self.adding_table[0].len());
+        let alphas = (1..(n + 1))
+            .map(|i| self.root_alpha(i as i32) as u32)
+            .collect();
+        let layers = common::get_layers_of(&alphas, self.field.order() as usize + 1);
 
         //retain unique
-        let mut unique_layers = Vec::new();
-        for l in layers.iter() {
-            if !unique_layers.contains(&l) {
-                unique_layers.push(l);
-            }
-        }
-
-        unique_layers.iter().for_each(|layer| {
-            let min_poly = common::calculate_layer_min_pol(layer, &self.adding_table);
-            for elem in layer.iter() {
-                if *elem <= n {
-                    min_pols[*elem as usize - 1] = min_poly.clone();
+        let mut unique_layers: Vec<(&Vec<u32>, BitVec)> = Vec::new();
+        let mut min_pols = Vec::new();
+        for layer in layers.iter() {
+            match unique_layers.iter().find(|(unique, _)| *unique == layer) {
+                Some((_, min_poly)) => min_pols.push(min_poly.clone()),
+                None => {
+                    let min_poly = common::calculate_layer_min_pol(layer, &self.field);
+                    min_pols.push(min_poly.clone());
+                    unique_layers.push((layer, min_poly));
                 }
             }
-        });
+        }
 
         min_pols
     }
 
+    /// Alpha power of the i-th consecutive root of the generator polynomial.
+    fn root_alpha(&self, i: i32) -> i32 {
+        self.field
+            .alpha_mod_order((self.first_root + i - 1) * self.root_step())
+    }
+
+    fn root_step(&self) -> i32 {
+        self.field.order() / self.cycle_len
+    }
+
     fn get_syndroms_alphas(&self, syndroms: &Vec<BitVec>) -> Vec<i32> {
         let mut syndroms_alphas = Vec::new();
         for (i, syndrome) in syndroms.iter().enumerate() {
             let mut alphas_to_add: Vec<i32> = Vec::new();
             for (j, coef) in syndrome.iter().rev().enumerate() {
                 if coef == true {
-                    alphas_to_add.push(self.alpha_mod_n((j * (i + 1)) as i32));
+                    alphas_to_add.push(
+                        self.field
+                            .alpha_mod_order(j as i32 * self.root_alpha(i as i32 + 1)),
+                    );
                 }
             }
             //TODO to doc: -1 means no element
             syndroms_alphas.push(
                 alphas_to_add
                     .iter()
-                    .fold(-1, |sum, alpha| self.add_alphas(sum, *alpha)),
+                    .fold(-1, |sum, alpha| self.field.add_alphas(sum, *alpha)),
             );
         }
 
         syndroms_alphas
     }
 
-    fn add_alphas(&self, a1: i32, a2: i32) -> i32 {
-        if a1 == -1 {
-            return a2;
-        } else if a2 == -1 {
-            return a1;
+    /// Syndromes consumed by one step of the algorithm. The binary shortcut
+    /// of two per step relies on S(2i) = S(i)^2, true only for narrow-sense
+    /// codes, otherwise every syndrome needs its own step.
+    fn syndroms_per_step(&self) -> i32 {
+        if self.first_root == 1 {
+            2
         } else {
-            return self.adding_table[a1 as usize][a2 as usize];
+            1
         }
     }
+}
 
-    fn init_table(&self, s1: i32) -> (Vec<f32>, Vec<Vec<i32>>, Vec<i32>, Vec<i32>, Vec<i32>) {
-        // prepare us
-        let mut us = Vec::new();
-        us.push(-0.5);
-        for i in 0..(self.t + 1) {
-            us.push(i as f32);
+impl Decoder for BerlekampDecoder {
+    fn decode(&self, encoded: &BitVec) -> Result<(BitVec, BitVec), String> {
+        if encoded.len() > self.n as usize || encoded.len() < (self.n - self.k) as usize {
+            return Err("Encoded data has wrong length!".to_owned());
         }
+        // shorter words come from shortened codes or messages shorter than k
+        let msg_len = encoded.len() - (self.n - self.k) as usize;
 
-        //prepare sigmas
-        let mut sigmas = Vec::new();
-        sigmas.push(vec![0]);
-        sigmas.push(vec![0]);
-
-        //prepare dus
-        let mut dus = Vec::new();
-        dus.push(0);
-        dus.push(s1);
-
-        //prepare lus
-        let mut lus = Vec::new();
-        lus.push(0);
-        lus.push(0);
-
-        //prepare dulus
-        let mut dulus = Vec::new();
-        dulus.push(-1);
-        dulus.push(0);
-
-        (us, sigmas, dus, lus, dulus)
-    }
-
-    fn find_dulu_idx(&self, dus: &Vec<i32>, dulus: &Vec<i32>) -> usize {
-        let mut max_value = -1;
-        let mut max_idx = 0;
-        for (i, (du, dulu)) in dus.iter().zip(dulus.iter()).enumerate() {
-            if i == dulus.len() - 1 {
-                break;
-            }
-            if *du != -1 && *dulu > max_value {
-                max_value = *dulu;
-                max_idx = i;
-            }
+        let syndroms = self.compute_syndroms(encoded);
+        if syndroms.iter().all(|syndrome| !syndrome.any()) {
+            return Ok((
+                encoded.iter().take(msg_len).collect(),
+                encoded.iter().skip(msg_len).collect(),
+            ));
         }
-        max_idx
-    }
+        let syndroms_alphas = self.get_syndroms_alphas(&syndroms);
 
-    fn alpha_mod_n(&self, alpha: i32) -> i32 {
-        let mut res = alpha % self.n;
-        if res < 0 {
-            res += self.n;
+        let (final_err_locator_poly, final_lu) =
+            find_error_locator(&self.field, &syndroms_alphas, self.syndroms_per_step());
+        let roots = self.field.find_roots(&final_err_locator_poly);
+        if roots.len() as i32 > self.t
+            || roots.len() != final_err_locator_poly.len() - 1
+            || roots.len() as i32 != final_lu
+        {
+            return Err("Too many errors. Could not decode".to_owned());
         }
-        res
-    }
-
-    //TODO to doc: alphas in sigmas cant be negative
-    fn multiply_sigma_by_alpha_with_x(
-        &self,
-        sigma: &Vec<i32>,
-        x_power: i32,
-        x_alpha: i32,
-    ) -> Vec<i32> {
-        let mut result = vec![-1; sigma.len() + x_power as usize];
-        for (i, alpha) in sigma.iter().rev().enumerate() {
-            let new_alpha;
-            if *alpha != -1 {
-                new_alpha = self.alpha_mod_n(x_alpha + *alpha);
-            } else {
-                new_alpha = -1;
+        let mut decoded = encoded.clone();
+        for root in roots {
+            // root is the inverse of the error locator, errors outside of
+            // a shortened word can not be corrected
+            let locator = self.field.alpha_mod_order(-root);
+            if locator % self.root_step() != 0 {
+                return Err("Error locator is not a code root power. Could not decode".to_owned());
+            }
+            let position = (locator / self.root_step()) as usize;
+            if position >= encoded.len() {
+                return Err("Error located outside of the code word. Could not decode".to_owned());
             }
-            *result.iter_mut().rev().nth(x_power as usize + i).unwrap() = new_alpha;
+            decoded.inverse_nth(encoded.len() - 1 - position);
         }
-        result
+        return Ok((
+            decoded.iter().take(msg_len).collect(),
+            decoded.iter().skip(msg_len).collect(),
+        ));
     }
+}
 
-    fn add_to_sigma(&self, sigma: Vec<i32>, to_add: Vec<i32>) -> Vec<i32> {
-        let (mut longer, shorter) = if sigma.len() >= to_add.len() {
-            (sigma, to_add)
+/// Berlekamp's iterative algorithm over the syndromes (as alpha powers),
+/// returns the error locator polynomial and its register length. Binary
+/// narrow-sense codes may consume two syndromes per step.
+pub fn find_error_locator(
+    field: &GaloisField,
+    syndroms_alphas: &Vec<i32>,
+    syndroms_per_step: i32,
+) -> (Vec<i32>, i32) {
+    let step = syndroms_per_step;
+    let steps = syndroms_alphas.len() as i32 / step;
+    let (us, mut sigmas, mut dus, mut lus, mut dulus) =
+        init_table(syndroms_alphas[0], steps, step);
+
+    let mut u_idx: usize = 1;
+    loop {
+        let u = us[u_idx];
+
+        let next_sigma;
+        let next_lu;
+        if dus[u_idx] == -1 {
+            next_sigma = sigmas[u_idx].clone();
+            next_lu = lus[u_idx];
+            sigmas.push(next_sigma.clone());
         } else {
-            (to_add, sigma)
-        };
-        for (longer_el, shorter_el) in longer.iter_mut().rev().zip(shorter.iter().rev()) {
-            if *longer_el == -1 {
-                *longer_el = *shorter_el;
-            } else if *shorter_el != -1 {
-                *longer_el = self.alpha_mod_n(self.add_alphas(*longer_el, *shorter_el));
-            }
-        }
-        longer
-    }
+            let most_positive_dulu_idx = find_dulu_idx(&dus, &dulus);
+            let up = us[most_positive_dulu_idx];
 
-    fn find_roots(&self, poly: &Vec<i32>) -> Vec<i32> {
-        let mut roots = Vec::new();
-        for i in 1..(self.n + 1) {
-            let mut mul_alphas_and_x = Vec::new();
-            for (j, alpha) in poly.iter().rev().enumerate() {
-                mul_alphas_and_x.push(self.alpha_mod_n(alpha + (i * j as i32)));
-            }
-            if mul_alphas_and_x
-                .iter()
-                .fold(-1, |sum, alpha| self.add_alphas(sum, *alpha))
-                == -1
-            {
-                roots.push(i);
-            }
-        }
-        roots
-    }
-}
+            let sigma_u = sigmas[u_idx].clone();
+            let du = dus[u_idx];
 
-impl Decoder for BerlekampDecoder {
-    fn decode(&self, encoded: &BitVec) -> Result<(BitVec, BitVec), String> {
-        let syndroms = self.compute_syndroms(encoded);
-        if syndroms.iter().all(|syndrome| !syndrome.any()) {
-            return Ok((
-                encoded.iter().take(self.k as usize).collect(),
-                encoded.iter().skip(self.k as usize).collect(),
-            ));
+            let dp_inv = dus[most_positive_dulu_idx] * -1;
+            let x_power = (step as f32 * (u as f32 - up)) as i32;
+            let sigma_p = sigmas[most_positive_dulu_idx].clone();
+
+            let x_alpha = field.alpha_mod_order(du + dp_inv);
+
+            let x_poly_sigma_p = field.multiply_poly_by_alpha_with_x(&sigma_p, x_power, x_alpha);
+            next_sigma = field.add_polys(sigma_u, x_poly_sigma_p);
+            // register length, the degree of sigma may be lower
+            next_lu = lus[u_idx].max(lus[most_positive_dulu_idx] + x_power);
+            sigmas.push(next_sigma.clone());
         }
-        let syndroms_alphas = self.get_syndroms_alphas(&syndroms);
+        lus.push(next_lu);
 
-        let (us, mut sigmas, mut dus, mut lus, mut dulus) = self.init_table(syndroms_alphas[0]);
+        if u as i32 + 1 == steps {
+            break;
+        }
 
-        let mut u_idx: usize = 1;
-        loop {
-            let u = us[u_idx];
+        let next_dulu = step * (u as i32 + 1) - next_lu;
+        dulus.push(next_dulu);
 
-            let next_sigma;
-            if dus[u_idx] == -1 {
-                next_sigma = sigmas[u_idx].clone();
-                sigmas.push(next_sigma.clone());
+        let next_syndrom_idx = (step * (u as i32 + 1)) as usize;
+        let mut alphas_to_add = Vec::new();
+        for i in 0..next_sigma.len() {
+            if i == 0 {
+                alphas_to_add.push(syndroms_alphas[next_syndrom_idx]); //TODO maybe f32
             } else {
-                let most_positive_dulu_idx = self.find_dulu_idx(&dus, &dulus);
-                let up = us[most_positive_dulu_idx];
+                alphas_to_add.push(field.multiply_alphas(
+                    syndroms_alphas[next_syndrom_idx - i],
+                    *next_sigma.iter().rev().nth(i).unwrap(),
+                ));
+            }
+        }
+        let next_du = alphas_to_add
+            .iter()
+            .fold(-1, |sum, alpha| field.add_alphas(sum, *alpha));
+        dus.push(next_du);
 
-                let sigma_u = sigmas[u_idx].clone();
-                let du = dus[u_idx];
+        u_idx += 1;
+    }
 
-                let dp_inv = dus[most_positive_dulu_idx] * -1;
-                let x_power = (2 as f32 * (u as f32 - up)) as i32;
-                let sigma_p = sigmas[most_positive_dulu_idx].clone();
+    (sigmas.pop().unwrap(), lus.pop().unwrap())
+}
 
-                let x_alpha = self.alpha_mod_n(du + dp_inv);
+fn init_table(s1: i32, steps: i32, step: i32) -> (Vec<f32>, Vec<Vec<i32>>, Vec<i32>, Vec<i32>, Vec<i32>) {
+    // prepare us
+    let mut us = Vec::new();
+    us.push(-1.0 / step as f32);
+    for i in 0..(steps + 1) {
+        us.push(i as f32);
+    }
 
-                let mut x_poly: Vec<i32> = vec![-1; x_power as usize + 1];
-                *x_poly.iter_mut().rev().nth(x_power as usize).unwrap() = x_alpha;
+    //prepare sigmas
+    let mut sigmas = Vec::new();
+    sigmas.push(vec![0]);
+    sigmas.push(vec![0]);
 
-                let x_poly_sigma_p =
-                    self.multiply_sigma_by_alpha_with_x(&sigma_p, x_power, x_alpha);
-                next_sigma = self.add_to_sigma(sigma_u, x_poly_sigma_p);
-                sigmas.push(next_sigma.clone());
-            }
+    //prepare dus
+    let mut dus = Vec::new();
+    dus.push(0);
+    dus.push(s1);
 
-            if u as i32 + 1 == self.t {
-                break;
-            }
+    //prepare lus
+    let mut lus = Vec::new();
+    lus.push(0);
+    lus.push(0);
 
-            let next_lu = next_sigma.len() as i32 - 1;
-            let next_dulu = 2 * (u as i32 + 1) - next_lu;
-            lus.push(next_lu);
-            dulus.push(next_dulu);
-
-            let L = next_lu;
-            let mut alphas_to_add = Vec::new();
-            for i in 0..(L + 1) {
-                if i == 0 {
-                    alphas_to_add.push(syndroms_alphas[2 * u as usize + 2]); //TODO maybe f32
-                } else {
-                    if *(next_sigma.iter().rev().nth(i as usize).unwrap()) != -1
-                        && syndroms_alphas[2 * u as usize + 2 - i as usize] != -1 {
-                        alphas_to_add.push(self.alpha_mod_n(
-                            syndroms_alphas[2 * u as usize + 2 - i as usize]
-                                + next_sigma.iter().rev().nth(i as usize).unwrap(),
-                        ));
-                    }
-                }
-            }
-            let next_du = alphas_to_add
-                .iter()
-                .fold(-1, |sum, alpha| self.add_alphas(sum, *alpha));
-            dus.push(next_du);
+    //prepare dulus
+    let mut dulus = Vec::new();
+    dulus.push(-1);
+    dulus.push(0);
 
-            u_idx += 1;
-        }
+    (us, sigmas, dus, lus, dulus)
+}
 
-        let final_err_locator_poly = sigmas.iter().last().unwrap();
-        let roots = self.find_roots(final_err_locator_poly);
-        if roots.len() as i32 > self.t {
-            return Err("Too many errors. Could not decode".to_owned());
+fn find_dulu_idx(dus: &Vec<i32>, dulus: &Vec<i32>) -> usize {
+    let mut max_value = -1;
+    let mut max_idx = 0;
+    for (i, (du, dulu)) in dus.iter().zip(dulus.iter()).enumerate() {
+        if i == dulus.len() - 1 {
+            break;
         }
-        let mut decoded = encoded.clone();
-        for root in roots {
-            decoded.inverse_nth(root as usize - 1);
+        if *du != -1 && *dulu > max_value {
+            max_value = *dulu;
+            max_idx = i;
         }
-        return Ok((
-            decoded.iter().take(self.k as usize).collect(),
-            decoded.iter().skip(self.k as usize).collect(),
-        ));
     }
+    max_idx
 }
 
 // fn println_layer(
@@ -331,36 +312,25 @@ mod tests {
     use super::*;
 
     #[test]
-    fn multiply_sigma_by_alpha_with_x_test() {
-        let decoder = BerlekampDecoder::new(31, 16, 3, &bitvec![1, 0, 0, 1, 0, 1]);
-
-        let sigma = vec![0];
-        let result = decoder.multiply_sigma_by_alpha_with_x(&sigma, 1, 2);
-        assert_eq!(result, vec![2, -1]);
-
-        let sigma = vec![2, 0];
-        let result = decoder.multiply_sigma_by_alpha_with_x(&sigma, 2, 25);
-        assert_eq!(result, vec![27, 25, -1, -1]);
-
-        let sigma = vec![25, 26, -1, 3];
-        let result = decoder.multiply_sigma_by_alpha_with_x(&sigma, 1, 25);
-        assert_eq!(result, vec![19, 20, -1, 28, -1]);
-    }
-
-    #[test]
-    fn add_to_sigma_test() {
-        let decoder = BerlekampDecoder::new(31, 16, 3, &bitvec![1, 0, 0, 1, 0, 1]);
-        let sigma = vec![24, 2, 0];
-        let to_add = vec![27, 25, -1, -1];
-        let result = decoder.add_to_sigma(sigma, to_add);
-        assert_eq!(result, vec![27, 11, 2, 0]);
+    fn find_error_locator_test() {
+        let field = GaloisField::new(&bitvec![1, 0, 0, 1, 0, 1]);
+        // errors at alpha^27, alpha^22 and alpha^9
+        let locators = vec![27, 22, 9];
+        let syndroms_alphas: Vec<i32> = (1..7)
+            .map(|j| {
+                locators.iter().fold(-1, |sum, locator| {
+                    field.add_alphas(sum, field.alpha_mod_order(locator * j))
+                })
+            })
+            .collect();
+
+        let (sigma, lu) = find_error_locator(&field, &syndroms_alphas, 2);
+        assert_eq!(lu, 3);
+        assert_eq!(field.find_roots(&sigma), vec![4, 9, 22]);
+
+        let (sigma, lu) = find_error_locator(&field, &syndroms_alphas, 1);
+        assert_eq!(lu, 3);
+        assert_eq!(field.find_roots(&sigma), vec![4, 9, 22]);
     }
 
-    #[test]
-    fn find_roots_test() {
-        let decoder = BerlekampDecoder::new(31, 16, 3, &bitvec![1, 0, 0, 1, 0, 1]);
-        let poly = vec![27, 11, 2, 0];
-        let result = decoder.find_roots(&poly);
-        assert_eq!(result, vec![4, 9, 22]);
-    }
 }
@@ -0,0 +1,226 @@
+use crate::bch_code::BchCode;
+use crate::weight_enumerator::{self, WeightDistribution};
+
+/// Analytical performance figures of a BCH code with a bounded-distance
+/// decoder correcting up to t errors. Figures depending on the weight
+/// distribution are None when the code is too large to enumerate.
+pub struct CodeBounds {
+    pub n: i32,
+    pub k: i32,
+    pub t: i32,
+    pub weights: Option<WeightDistribution>,
+}
+
+impl CodeBounds {
+    pub fn new(code: &BchCode) -> CodeBounds {
+        CodeBounds {
+            n: code.n,
+            k: code.k,
+            t: code.t,
+            weights: weight_enumerator::weight_distribution(code).ok(),
+        }
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.k as f64 / self.n as f64
+    }
+
+    /// Probability of more than t errors in a block on a BSC with crossover p.
+    pub fn bsc_block_error_rate(&self, p: f64) -> f64 {
+        binomial_terms(self.n, p)
+            .iter()
+            .skip(self.t as usize + 1)
+            .sum()
+    }
+
+    /// Approximate bit error rate after decoding. A block with i > t errors
+    /// is assumed to leave at most i + t bits wrong.
+    pub fn bsc_bit_error_rate(&self, p: f64) -> f64 {
+        binomial_terms(self.n, p)
+            .iter()
+            .enumerate()
+            .skip(self.t as usize + 1)
+            .map(|(i, term)| (i as i32 + self.t).min(self.n) as f64 * term)
+            .sum::<f64>()
+            / self.n as f64
+    }
+
+    pub fn bsc_undetected_error_rate(&self, p: f64) -> Option<f64> {
+        self.weights
+            .as_ref()
+            .map(|weights| weights.undetected_error_probability(p))
+    }
+
+    /// Block error rate of hard decisions on BPSK over AWGN fed to the
+    /// t-error decoder.
+    pub fn awgn_hard_block_error_rate(&self, eb_n0_db: f64) -> f64 {
+        self.bsc_block_error_rate(self.awgn_crossover(eb_n0_db))
+    }
+
+    /// Crossover probability of hard decisions on coded BPSK symbols.
+    pub fn awgn_crossover(&self, eb_n0_db: f64) -> f64 {
+        q_function((2.0 * self.rate() * db_to_linear(eb_n0_db)).sqrt())
+    }
+
+    /// Union bound on the block error rate of soft ML decoding of BPSK over
+    /// AWGN, sum of A_w Q(sqrt(2 w R Eb/N0)).
+    pub fn awgn_union_block_error_rate(&self, eb_n0_db: f64) -> Option<f64> {
+        self.awgn_union_sum(eb_n0_db, |_| 1.0)
+    }
+
+    /// Union bound on the bit error rate, every weight w term scaled by w/n.
+    pub fn awgn_union_bit_error_rate(&self, eb_n0_db: f64) -> Option<f64> {
+        let n = self.n as f64;
+        self.awgn_union_sum(eb_n0_db, |w| w as f64 / n)
+    }
+
+    fn awgn_union_sum<F: Fn(usize) -> f64>(&self, eb_n0_db: f64, scale: F) -> Option<f64> {
+        let snr = self.rate() * db_to_linear(eb_n0_db);
+        self.weights.as_ref().map(|weights| {
+            weights
+                .counts
+                .iter()
+                .enumerate()
+                .skip(1)
+                .filter(|(_, count)| **count != 0)
+                .map(|(w, count)| {
+                    scale(w) * *count as f64 * q_function((2.0 * w as f64 * snr).sqrt())
+                })
+                .sum::<f64>()
+                .min(1.0)
+        })
+    }
+}
+
+/// Probabilities of exactly i errors in n bits, i = 0..=n.
+fn binomial_terms(n: i32, p: f64) -> Vec<f64> {
+    let mut terms = vec![0.0; n as usize + 1];
+    if p <= 0.0 {
+        terms[0] = 1.0;
+        return terms;
+    }
+    if p >= 1.0 {
+        terms[n as usize] = 1.0;
+        return terms;
+    }
+    // in logarithms, binomial coefficients of long codes overflow
+    let mut ln_binomial = 0.0;
+    for i in 0..(n as usize + 1) {
+        terms[i] = (ln_binomial + i as f64 * p.ln() + (n as usize - i) as f64 * (-p).ln_1p()).exp();
+        ln_binomial += ((n as usize - i) as f64).ln() - ((i + 1) as f64).ln();
+    }
+    terms
+}
+
+pub fn db_to_linear(db: f64) -> f64 {
+    10f64.powf(db / 10.0)
+}
+
+/// Tail probability of the standard normal distribution.
+pub fn q_function(x: f64) -> f64 {
+    0.5 * erfc(x / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function, Chebyshev fit with relative error below
+/// 1.2e-7 (Numerical Recipes erfcc).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807
+                            + t * (-1.13520398
+                                + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let result = t * poly.exp();
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitvec::prelude::*;
+
+    fn get_hamming_bounds() -> CodeBounds {
+        CodeBounds::new(&BchCode::new(7, 4, 1, &bitvec![1, 0, 1, 1]))
+    }
+
+    fn assert_close(result: f64, expected: f64) {
+        assert!(
+            (result - expected).abs() <= 1e-6 * expected.abs(),
+            "{} != {}",
+            result,
+            expected
+        );
+    }
+
+    #[test]
+    fn q_function_test() {
+        assert_close(q_function(0.0), 0.5);
+        assert_close(q_function(1.0), 0.158655254);
+        assert_close(q_function(3.0), 1.349898e-3);
+        assert_close(q_function(-1.0), 0.841344746);
+    }
+
+    #[test]
+    fn bsc_block_error_rate_test() {
+        let bounds = get_hamming_bounds();
+        let p: f64 = 0.01;
+        let expected = 1.0 - (1.0 - p).powi(7) - 7.0 * p * (1.0 - p).powi(6);
+        assert_close(bounds.bsc_block_error_rate(p), expected);
+        assert_eq!(bounds.bsc_block_error_rate(0.0), 0.0);
+    }
+
+    #[test]
+    fn bsc_bit_error_rate_test() {
+        let bounds = get_hamming_bounds();
+        let p: f64 = 0.01;
+        let expected = (1..7).fold(0.0, |sum, i| {
+            let binomial = [1.0, 7.0, 21.0, 35.0, 35.0, 21.0, 7.0, 1.0][i + 1];
+            sum + ((i + 2).min(7) as f64)
+                * binomial
+                * p.powi(i as i32 + 1)
+                * (1.0 - p).powi(6 - i as i32)
+        }) / 7.0;
+        assert_close(bounds.bsc_bit_error_rate(p), expected);
+        assert!(bounds.bsc_bit_error_rate(p) < p);
+    }
+
+    #[test]
+    fn long_code_bsc_test() {
+        let bounds = CodeBounds {
+            n: 16383,
+            k: 16383 - 14 * 40,
+            t: 40,
+            weights: None,
+        };
+        let result = bounds.bsc_block_error_rate(1e-3);
+        assert!(result > 0.0 && result < 1e-6);
+        assert_eq!(bounds.awgn_union_block_error_rate(5.0), None);
+    }
+
+    #[test]
+    fn awgn_union_bound_test() {
+        let bounds = get_hamming_bounds();
+        let eb_n0_db = 6.0;
+        let snr = 4.0 / 7.0 * db_to_linear(eb_n0_db);
+        let expected = 7.0 * q_function((6.0 * snr).sqrt())
+            + 7.0 * q_function((8.0 * snr).sqrt())
+            + q_function((14.0 * snr).sqrt());
+        assert_close(
+            bounds.awgn_union_block_error_rate(eb_n0_db).unwrap(),
+            expected,
+        );
+        assert!(
+            bounds.awgn_union_bit_error_rate(eb_n0_db).unwrap()
+                < bounds.awgn_union_block_error_rate(eb_n0_db).unwrap()
+        );
+        assert!(bounds.awgn_hard_block_error_rate(eb_n0_db) > expected);
+    }
+}
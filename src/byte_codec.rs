@@ -0,0 +1,572 @@
+use crate::bch_code::BchCode;
+use crate::berlekamp_decoder::BerlekampDecoder;
+use crate::common;
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use bitvec::prelude::*;
+
+/// Order of the bits within a byte or u64 word. With MsbFirst the most
+/// significant bit is the first bit of the message, the highest degree
+/// coefficient of the code word polynomial. With LsbFirst the least
+/// significant bit is the first one. Words of u64 are taken big endian with
+/// MsbFirst and little endian with LsbFirst, so bit 63 respectively bit 0 of
+/// the first word comes first.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BitOrder {
+    MsbFirst,
+    LsbFirst,
+}
+
+/// Orientation of a polynomial given as a bit sequence. The crate itself
+/// always stores the highest degree coefficient first, so bitvec![1, 0, 1, 1]
+/// is x^3 + x + 1. With LowestFirst the same bits are 1 + x^2 + x^3.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PolyOrder {
+    HighestFirst,
+    LowestFirst,
+}
+
+impl PolyOrder {
+    /// The polynomial with the highest degree coefficient first.
+    pub fn normalize(&self, poly: &BitVec) -> BitVec {
+        match self {
+            PolyOrder::HighestFirst => poly.clone(),
+            PolyOrder::LowestFirst => poly.iter().rev().collect(),
+        }
+    }
+}
+
+/// Place of the parity bits in a serialized code word.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParityPosition {
+    First,
+    Last,
+}
+
+/// How a systematic code word c(x) = m(x) x^(n - k) + r(x) is serialized.
+/// The message m(x) and the parity r(x) are sent as two bit sequences, each
+/// with the coefficients in poly_order, the parity before or after the
+/// message, and every byte packed in bit_order. Reversing bit_order swaps
+/// the bits within each byte, LowestFirst with parity First reverses the
+/// whole code word.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Conventions {
+    pub bit_order: BitOrder,
+    pub poly_order: PolyOrder,
+    pub parity: ParityPosition,
+}
+
+impl Conventions {
+    /// Highest degree first, parity last, the layout of Encoder.
+    pub fn new(bit_order: BitOrder) -> Conventions {
+        Conventions {
+            bit_order: bit_order,
+            poly_order: PolyOrder::HighestFirst,
+            parity: ParityPosition::Last,
+        }
+    }
+
+    pub fn with_poly_order(mut self, poly_order: PolyOrder) -> Conventions {
+        self.poly_order = poly_order;
+        self
+    }
+
+    pub fn with_parity(mut self, parity: ParityPosition) -> Conventions {
+        self.parity = parity;
+        self
+    }
+
+    /// Serialized bits of the code word of a msg_len bit message.
+    fn serialize(&self, word: &BitVec, msg_len: usize) -> BitVec {
+        let msg = self
+            .poly_order
+            .normalize(&word.iter().take(msg_len).collect());
+        let parity = self
+            .poly_order
+            .normalize(&word.iter().skip(msg_len).collect());
+        match self.parity {
+            ParityPosition::First => parity.iter().chain(msg.iter()).collect(),
+            ParityPosition::Last => msg.iter().chain(parity.iter()).collect(),
+        }
+    }
+
+    /// Code word of serialized bits holding a msg_len bit message.
+    fn deserialize(&self, bits: &BitVec, msg_len: usize) -> BitVec {
+        let parity_len = bits.len() - msg_len;
+        let (msg, parity): (BitVec, BitVec) = match self.parity {
+            ParityPosition::First => (
+                bits.iter().skip(parity_len).collect(),
+                bits.iter().take(parity_len).collect(),
+            ),
+            ParityPosition::Last => (
+                bits.iter().take(msg_len).collect(),
+                bits.iter().skip(msg_len).collect(),
+            ),
+        };
+        let msg = self.poly_order.normalize(&msg);
+        let parity = self.poly_order.normalize(&parity);
+        msg.iter().chain(parity.iter()).collect()
+    }
+}
+
+/// Bits of the bytes in the given order.
+pub fn bytes_to_bits(bytes: &[u8], order: BitOrder) -> BitVec {
+    bytes
+        .iter()
+        .flat_map(|byte| {
+            (0..8).map(move |i| match order {
+                BitOrder::MsbFirst => byte & (0x80 >> i) != 0,
+                BitOrder::LsbFirst => byte & (1 << i) != 0,
+            })
+        })
+        .collect()
+}
+
+/// Bytes of the bits in the given order, the last one zero padded.
+pub fn bits_to_bytes(bits: &BitVec, order: BitOrder) -> Vec<u8> {
+    let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+    for (i, bit) in bits.iter().by_vals().enumerate() {
+        if bit {
+            bytes[i / 8] |= match order {
+                BitOrder::MsbFirst => 0x80 >> (i % 8),
+                BitOrder::LsbFirst => 1 << (i % 8),
+            };
+        }
+    }
+    bytes
+}
+
+fn words_to_bytes(words: &[u64], order: BitOrder) -> Vec<u8> {
+    words
+        .iter()
+        .flat_map(|word| match order {
+            BitOrder::MsbFirst => word.to_be_bytes(),
+            BitOrder::LsbFirst => word.to_le_bytes(),
+        })
+        .collect()
+}
+
+fn bytes_to_words(bytes: &[u8], order: BitOrder) -> Vec<u64> {
+    bytes
+        .chunks(8)
+        .map(|chunk| {
+            let mut word = [0u8; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            match order {
+                BitOrder::MsbFirst => u64::from_be_bytes(word),
+                BitOrder::LsbFirst => u64::from_le_bytes(word),
+            }
+        })
+        .collect()
+}
+
+/// Polynomial of the bits of value, bit i being the coefficient of x^i.
+pub fn poly_from_u32(value: u32) -> BitVec {
+    let degree = 31 - value.leading_zeros() as i32;
+    (0..=degree).rev().map(|i| value & (1 << i) != 0).collect()
+}
+
+pub fn poly_to_u32(poly: &BitVec) -> u32 {
+    poly.iter()
+        .by_vals()
+        .fold(0u32, |value, bit| (value << 1) | bit as u32)
+}
+
+/// Encodes and decodes plain bytes and u64 words. A code word holds the
+/// message bits and the n - k parity bits laid out by the conventions of
+/// the codec, zero padded to whole bytes (words). Messages shorter than k
+/// bits are encoded with the shortened code.
+pub struct ByteCodec {
+    code: BchCode,
+    conventions: Conventions,
+    encoder: Encoder,
+    decoder: Box<dyn Decoder + Send + Sync>,
+}
+
+impl ByteCodec {
+    pub fn new(code: &BchCode, order: BitOrder) -> ByteCodec {
+        ByteCodec {
+            code: code.clone(),
+            conventions: Conventions::new(order),
+            encoder: Encoder::from_code(code),
+            decoder: Box::new(BerlekampDecoder::from_code(code)),
+        }
+    }
+
+    /// Primitive narrow-sense code of length 2^m - 1 correcting t errors,
+    /// prime_poly given with bit i as the coefficient of x^i. Fails when no
+    /// message bit is left.
+    pub fn primitive(
+        m: u32,
+        t: i32,
+        prime_poly: u32,
+        order: BitOrder,
+    ) -> Result<ByteCodec, String> {
+        if !(common::MIN_M..=common::MAX_M).contains(&m)
+            || prime_poly >> m != 1
+            || !common::is_primitive_poly(&poly_from_u32(prime_poly))
+        {
+            return Err(format!(
+                "Bad code parameters. m: {}, prime poly: {:#x}",
+                m, prime_poly
+            ));
+        }
+        let n = (1 << m) - 1;
+        if t < 1 || t > n / 2 {
+            return Err(format!("Bad code parameters. n: {}, t: {}", n, t));
+        }
+        let code = BchCode::new(n, 0, t, &poly_from_u32(prime_poly)).with_derived_k();
+        if code.k <= 0 {
+            return Err(format!(
+                "Code of length {} correcting {} errors has no message bits",
+                n, t
+            ));
+        }
+        Ok(ByteCodec::new(&code, order))
+    }
+
+    /// Same codec serializing code words by the given conventions.
+    pub fn with_conventions(mut self, conventions: Conventions) -> ByteCodec {
+        self.conventions = conventions;
+        self
+    }
+
+    /// Same codec decoding with the given decoder of code().
+    pub fn with_decoder(mut self, decoder: Box<dyn Decoder + Send + Sync>) -> ByteCodec {
+        self.decoder = decoder;
+        self
+    }
+
+    pub fn code(&self) -> &BchCode {
+        &self.code
+    }
+
+    pub fn conventions(&self) -> &Conventions {
+        &self.conventions
+    }
+
+    pub fn order(&self) -> BitOrder {
+        self.conventions.bit_order
+    }
+
+    fn parity_bits(&self) -> usize {
+        (self.code.n - self.code.k) as usize
+    }
+
+    /// Bytes added to a message by encode.
+    pub fn parity_bytes(&self) -> usize {
+        self.parity_bits().div_ceil(8)
+    }
+
+    /// Words added to a message by encode_words.
+    pub fn parity_words(&self) -> usize {
+        self.parity_bits().div_ceil(64)
+    }
+
+    /// Only the parity bits of the message, packed on their own.
+    pub fn parity(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let word = self.encode_bits(data)?;
+        let parity = match self.conventions.parity {
+            ParityPosition::First => word.iter().take(self.parity_bits()).collect(),
+            ParityPosition::Last => word.iter().skip(data.len() * 8).collect(),
+        };
+        Ok(bits_to_bytes(&parity, self.order()))
+    }
+
+    /// Code word of the message, data.len() + parity_bytes() bytes long.
+    pub fn encode(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        Ok(bits_to_bytes(&self.encode_bits(data)?, self.order()))
+    }
+
+    /// Message of a code word made by encode and the number of corrected
+    /// bits.
+    pub fn decode(&self, word: &[u8]) -> Result<(Vec<u8>, usize), String> {
+        let (msg, errors) = self.decode_bits(word)?;
+        Ok((bits_to_bytes(&msg, self.order()), errors.len()))
+    }
+
+    /// Corrected bits of a code word made by encode, bit p being the p-th
+    /// bit of the serialized word.
+    pub fn locate_errors(&self, word: &[u8]) -> Result<Vec<usize>, String> {
+        Ok(self.decode_bits(word)?.1)
+    }
+
+    fn decode_bits(&self, word: &[u8]) -> Result<(BitVec, Vec<usize>), String> {
+        let data_len = word
+            .len()
+            .checked_sub(self.parity_bytes())
+            .ok_or_else(|| "Code word is shorter than the parity!".to_owned())?;
+        let msg_len = data_len * 8;
+        let received: BitVec = bytes_to_bits(word, self.order())
+            .iter()
+            .take(msg_len + self.parity_bits())
+            .collect();
+        let (msg, _) = self
+            .decoder
+            .decode(&self.conventions.deserialize(&received, msg_len))?;
+        let sent = self
+            .conventions
+            .serialize(&self.encoder.encode(&msg)?, msg_len);
+        let errors = received
+            .iter()
+            .zip(sent.iter())
+            .enumerate()
+            .filter(|(_, (received, sent))| received != sent)
+            .map(|(i, _)| i)
+            .collect();
+        Ok((self.conventions.poly_order.normalize(&msg), errors))
+    }
+
+    /// Code word of a message of u64 words, data.len() + parity_words()
+    /// words long.
+    pub fn encode_words(&self, data: &[u64]) -> Result<Vec<u64>, String> {
+        let word = self.encode(&words_to_bytes(data, self.order()))?;
+        Ok(bytes_to_words(&word, self.order()))
+    }
+
+    pub fn decode_words(&self, word: &[u64]) -> Result<(Vec<u64>, usize), String> {
+        let data_len = word
+            .len()
+            .checked_sub(self.parity_words())
+            .ok_or_else(|| "Code word is shorter than the parity!".to_owned())?;
+        let mut bytes = words_to_bytes(word, self.order());
+        bytes.truncate(data_len * 8 + self.parity_bytes());
+        let (msg, corrected_bits) = self.decode(&bytes)?;
+        Ok((bytes_to_words(&msg, self.order()), corrected_bits))
+    }
+
+    fn encode_bits(&self, data: &[u8]) -> Result<BitVec, String> {
+        if data.len() * 8 > self.code.k as usize {
+            return Err(format!(
+                "Message of {} bytes does not fit in k: {} bits",
+                data.len(),
+                self.code.k
+            ));
+        }
+        let msg = bytes_to_bits(data, self.order());
+        let word = self
+            .encoder
+            .encode(&self.conventions.poly_order.normalize(&msg))?;
+        Ok(self.conventions.serialize(&word, msg.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framing;
+
+    fn get_codec(order: BitOrder) -> ByteCodec {
+        // BCH(63, 39) t = 4, x^6 + x + 1
+        ByteCodec::primitive(6, 4, 0x43, order).unwrap()
+    }
+
+    fn flip(word: &mut [u8], bit: usize) {
+        word[bit / 8] ^= 1 << (bit % 8);
+    }
+
+    #[test]
+    fn primitive_test() {
+        let order = BitOrder::MsbFirst;
+        assert_eq!(
+            ByteCodec::primitive(5, 15, 0x25, order).unwrap().code().k,
+            1
+        );
+        assert!(ByteCodec::primitive(5, 16, 0x25, order).is_err());
+        assert!(ByteCodec::primitive(5, 0, 0x25, order).is_err());
+        // x^5 + 1 is not primitive
+        assert!(ByteCodec::primitive(5, 2, 0x21, order).is_err());
+        assert!(ByteCodec::primitive(6, 2, 0x25, order).is_err());
+    }
+
+    #[test]
+    fn bit_order_test() {
+        let bytes = [0b1100_0001, 0x80];
+        assert_eq!(
+            bytes_to_bits(&bytes, BitOrder::MsbFirst),
+            framing::bytes_to_bits(&bytes)
+        );
+        assert_eq!(
+            bytes_to_bits(&bytes, BitOrder::LsbFirst),
+            bitvec![1, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 1]
+        );
+        for order in &[BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            assert_eq!(bits_to_bytes(&bytes_to_bits(&bytes, *order), *order), bytes);
+        }
+        assert_eq!(
+            bits_to_bytes(&bitvec![1, 1], BitOrder::LsbFirst),
+            vec![0b11]
+        );
+    }
+
+    fn kat_codec(conventions: Conventions) -> ByteCodec {
+        // BCH(31, 16) t = 3, generator 107657 octal
+        ByteCodec::primitive(5, 3, 0x25, BitOrder::MsbFirst)
+            .unwrap()
+            .with_conventions(conventions)
+    }
+
+    fn assert_kat(conventions: Conventions, word: &[u8], parity: &[u8]) {
+        let codec = kat_codec(conventions);
+        let data = [0xa5, 0x3c];
+        assert_eq!(codec.encode(&data).unwrap(), word, "{:?}", conventions);
+        assert_eq!(codec.parity(&data).unwrap(), parity, "{:?}", conventions);
+        assert_eq!(codec.decode(word), Ok((data.to_vec(), 0)));
+        for bit in 0..31 {
+            let mut corrupted = word.to_vec();
+            corrupted[bit / 8] ^= match conventions.bit_order {
+                BitOrder::MsbFirst => 0x80 >> (bit % 8),
+                BitOrder::LsbFirst => 1 << (bit % 8),
+            };
+            assert_eq!(codec.decode(&corrupted), Ok((data.to_vec(), 1)));
+        }
+    }
+
+    #[test]
+    fn poly_order_test() {
+        let poly = bitvec![1, 1, 0, 1];
+        assert_eq!(PolyOrder::HighestFirst.normalize(&poly), poly);
+        assert_eq!(PolyOrder::LowestFirst.normalize(&poly), bitvec![1, 0, 1, 1]);
+    }
+
+    #[test]
+    fn default_conventions_known_answer_test() {
+        let conventions = Conventions::new(BitOrder::MsbFirst);
+        assert_kat(conventions, &[0xa5, 0x3c, 0x15, 0x02], &[0x15, 0x02]);
+    }
+
+    #[test]
+    fn parity_first_known_answer_test() {
+        let conventions = Conventions::new(BitOrder::MsbFirst).with_parity(ParityPosition::First);
+        assert_kat(conventions, &[0x15, 0x03, 0x4a, 0x78], &[0x15, 0x02]);
+    }
+
+    #[test]
+    fn lowest_first_known_answer_test() {
+        let conventions =
+            Conventions::new(BitOrder::MsbFirst).with_poly_order(PolyOrder::LowestFirst);
+        assert_kat(conventions, &[0xa5, 0x3c, 0x7a, 0xa8], &[0x7a, 0xa8]);
+        let conventions = conventions.with_parity(ParityPosition::First);
+        assert_kat(conventions, &[0x7a, 0xa9, 0x4a, 0x78], &[0x7a, 0xa8]);
+    }
+
+    #[test]
+    fn bit_reversal_known_answer_test() {
+        let conventions = Conventions::new(BitOrder::LsbFirst);
+        assert_kat(conventions, &[0xa5, 0x3c, 0xa8, 0x40], &[0xa8, 0x40]);
+    }
+
+    #[test]
+    fn reversed_code_word_test() {
+        // lowest degree first with parity first sends the code word backwards
+        let data = [0x12u8, 0x34, 0x56];
+        let reversed_data: Vec<u8> = data.iter().rev().map(|byte| byte.reverse_bits()).collect();
+        let codec = get_codec(BitOrder::MsbFirst);
+        let backwards = get_codec(BitOrder::MsbFirst).with_conventions(
+            Conventions::new(BitOrder::MsbFirst)
+                .with_poly_order(PolyOrder::LowestFirst)
+                .with_parity(ParityPosition::First),
+        );
+        let word = bytes_to_bits(&codec.encode(&data).unwrap(), BitOrder::MsbFirst);
+        let word: BitVec = word.iter().take(48).collect();
+        let expected: BitVec = word.iter().rev().collect();
+        let backwards_word = bytes_to_bits(
+            &backwards.encode(&reversed_data).unwrap(),
+            BitOrder::MsbFirst,
+        );
+        let backwards_word: BitVec = backwards_word.iter().take(48).collect();
+        assert_eq!(backwards_word, expected);
+    }
+
+    #[test]
+    fn poly_u32_test() {
+        assert_eq!(poly_from_u32(0x43), bitvec![1, 0, 0, 0, 0, 1, 1]);
+        assert_eq!(poly_to_u32(&bitvec![1, 0, 0, 1, 0, 1]), 0x25);
+    }
+
+    #[test]
+    fn encode_matches_encoder_test() {
+        let codec = get_codec(BitOrder::MsbFirst);
+        assert_eq!((codec.code().n, codec.code().k), (63, 39));
+        assert_eq!(codec.parity_bytes(), 3);
+        let data = [0xde, 0xad, 0xbe, 0xef];
+        let expected = Encoder::from_code(codec.code())
+            .encode(&framing::bytes_to_bits(&data))
+            .unwrap();
+        let word = codec.encode(&data).unwrap();
+        assert_eq!(word, framing::bits_to_bytes(&expected));
+        assert_eq!(&word[..4], &data);
+        assert_eq!(codec.parity(&data).unwrap(), &word[4..]);
+    }
+
+    #[test]
+    fn lsb_first_reverses_bits_test() {
+        let data = [0x12, 0x34, 0x56];
+        let msb = get_codec(BitOrder::MsbFirst).encode(&data).unwrap();
+        let reversed: Vec<u8> = data.iter().map(|byte| byte.reverse_bits()).collect();
+        let lsb = get_codec(BitOrder::LsbFirst).encode(&reversed).unwrap();
+        let lsb: Vec<u8> = lsb.iter().map(|byte| byte.reverse_bits()).collect();
+        assert_eq!(lsb, msb);
+    }
+
+    #[test]
+    fn decode_corrects_errors_test() {
+        for order in &[BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let codec = get_codec(*order);
+            for len in 0..5 {
+                let data: Vec<u8> = (0..len).map(|i| (i * 73 + 5) as u8).collect();
+                let mut word = codec.encode(&data).unwrap();
+                assert_eq!(word.len(), len + 3);
+                let bits = len * 8 + 24;
+                for bit in &[0, bits / 2, bits - 1] {
+                    flip(&mut word, *bit);
+                }
+                assert_eq!(codec.decode(&word), Ok((data, 3)));
+            }
+        }
+    }
+
+    #[test]
+    fn locate_errors_test() {
+        let conventions = Conventions::new(BitOrder::LsbFirst).with_parity(ParityPosition::First);
+        let codec = get_codec(BitOrder::LsbFirst).with_conventions(conventions);
+        let mut word = codec.encode(&[7, 8]).unwrap();
+        assert_eq!(codec.locate_errors(&word), Ok(vec![]));
+        for bit in &[3, 17, 39] {
+            flip(&mut word, *bit);
+        }
+        assert_eq!(codec.locate_errors(&word), Ok(vec![3, 17, 39]));
+    }
+
+    #[test]
+    fn decode_fails_test() {
+        let codec = get_codec(BitOrder::MsbFirst);
+        assert!(codec.encode(&[0; 5]).is_err());
+        assert!(codec.decode(&[0; 2]).is_err());
+        let mut word = codec.encode(&[1, 2, 3, 4]).unwrap();
+        for bit in 0..7 {
+            flip(&mut word, bit * 8);
+        }
+        assert!(codec.decode(&word).is_err());
+    }
+
+    #[test]
+    fn words_test() {
+        // BCH(255, 131) t = 18 has 124 parity bits, two words
+        let data = [0x0123_4567_89ab_cdef, 0xfedc_ba98_7654_3210];
+        for order in &[BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            let codec = ByteCodec::primitive(8, 18, 0x11d, *order).unwrap();
+            assert_eq!(codec.code().k, 131);
+            assert_eq!(codec.parity_words(), 2);
+            let bytes = words_to_bytes(&data, *order);
+            let expected = bytes_to_words(&codec.encode(&bytes).unwrap(), *order);
+            let mut word = codec.encode_words(&data).unwrap();
+            assert_eq!(word, expected);
+            assert_eq!(&word[..2], &data);
+
+            word[0] ^= 1 << 63 | 1;
+            word[3] ^= 1 << 40;
+            assert_eq!(codec.decode_words(&word), Ok((data.to_vec(), 3)));
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use crate::common;
+use bitvec::prelude::*;
+use rand::distributions::{Distribution, Normal};
+use rand::{Rng, RngCore};
+
+/// Binary channel corrupting transmitted words. Randomness comes from the
+/// caller so runs can be replayed from a seed.
+pub trait Channel {
+    fn transmit(&mut self, word: &BitVec, rng: &mut dyn RngCore) -> BitVec;
+}
+
+/// Flips every bit independently with probability p.
+pub struct BinarySymmetricChannel {
+    p: f64,
+}
+
+impl BinarySymmetricChannel {
+    pub fn new(p: f64) -> BinarySymmetricChannel {
+        if p < 0.0 || p > 1.0 {
+            panic!("Bad channel parameters. p: {}", p);
+        }
+        BinarySymmetricChannel { p: p }
+    }
+}
+
+impl Channel for BinarySymmetricChannel {
+    fn transmit(&mut self, word: &BitVec, rng: &mut dyn RngCore) -> BitVec {
+        word.iter()
+            .by_vals()
+            .map(|bit| bit ^ rng.gen_bool(self.p))
+            .collect()
+    }
+}
+
+/// Flips exactly `errors` distinct bits of every word.
+pub struct FixedErrorsChannel {
+    errors: usize,
+}
+
+impl FixedErrorsChannel {
+    pub fn new(errors: usize) -> FixedErrorsChannel {
+        FixedErrorsChannel { errors: errors }
+    }
+}
+
+impl Channel for FixedErrorsChannel {
+    fn transmit(&mut self, word: &BitVec, rng: &mut dyn RngCore) -> BitVec {
+        let mut received = word.clone();
+        let errors = self.errors.min(word.len());
+        for place in common::get_random_places(errors as i32, word.len() as i32, rng) {
+            let bit = received[place];
+            received.set(place, !bit);
+        }
+        received
+    }
+}
+
+/// BPSK over AWGN, bit 0 sent as +1 and bit 1 as -1. The noise variance
+/// follows from Eb/N0 in dB and the code rate.
+pub struct AwgnBpskChannel {
+    noise: Normal,
+    variance: f64,
+}
+
+impl AwgnBpskChannel {
+    pub fn new(eb_n0_db: f64, rate: f64) -> AwgnBpskChannel {
+        if rate <= 0.0 || rate > 1.0 {
+            panic!("Bad channel parameters. rate: {}", rate);
+        }
+        let variance = 1.0 / (2.0 * rate * 10f64.powf(eb_n0_db / 10.0));
+        AwgnBpskChannel {
+            noise: Normal::new(0.0, variance.sqrt()),
+            variance: variance,
+        }
+    }
+
+    /// Log-likelihood ratios ln(P(0|y) / P(1|y)) = 2y / variance of the
+    /// received symbols, positive values favour 0.
+    pub fn llrs(&mut self, word: &BitVec, rng: &mut dyn RngCore) -> Vec<f64> {
+        word.iter()
+            .by_vals()
+            .map(|bit| {
+                let symbol = if bit { -1.0 } else { 1.0 };
+                2.0 * (symbol + self.noise.sample(rng)) / self.variance
+            })
+            .collect()
+    }
+}
+
+impl Channel for AwgnBpskChannel {
+    /// Hard decisions on the LLRs.
+    fn transmit(&mut self, word: &BitVec, rng: &mut dyn RngCore) -> BitVec {
+        self.llrs(word, rng).iter().map(|llr| *llr < 0.0).collect()
+    }
+}
+
+/// One burst of exactly `length` bits per word at a random position. The
+/// first and last bits of the burst are flipped, the ones between with
+/// probability 1/2.
+pub struct BurstChannel {
+    length: usize,
+}
+
+impl BurstChannel {
+    pub fn new(length: usize) -> BurstChannel {
+        BurstChannel { length: length }
+    }
+}
+
+impl Channel for BurstChannel {
+    fn transmit(&mut self, word: &BitVec, rng: &mut dyn RngCore) -> BitVec {
+        let mut received = word.clone();
+        let length = self.length.min(word.len());
+        if length == 0 {
+            return received;
+        }
+        let start = rng.gen_range(0, word.len() - length + 1);
+        for place in start..(start + length) {
+            if place == start || place == start + length - 1 || rng.gen::<bool>() {
+                let bit = received[place];
+                received.set(place, !bit);
+            }
+        }
+        received
+    }
+}
+
+/// Two-state Markov channel. Every bit first moves the state (good to bad
+/// with p_good_to_bad, back with p_bad_to_good), then is flipped with the
+/// error probability of the state. The state carries over between words,
+/// starting in the good one.
+pub struct GilbertElliottChannel {
+    p_good_to_bad: f64,
+    p_bad_to_good: f64,
+    good_error: f64,
+    bad_error: f64,
+    bad: bool,
+}
+
+impl GilbertElliottChannel {
+    pub fn new(
+        p_good_to_bad: f64,
+        p_bad_to_good: f64,
+        good_error: f64,
+        bad_error: f64,
+    ) -> GilbertElliottChannel {
+        let probabilities = [p_good_to_bad, p_bad_to_good, good_error, bad_error];
+        if probabilities.iter().any(|p| *p < 0.0 || *p > 1.0) {
+            panic!("Bad channel parameters. {:?}", probabilities);
+        }
+        GilbertElliottChannel {
+            p_good_to_bad: p_good_to_bad,
+            p_bad_to_good: p_bad_to_good,
+            good_error: good_error,
+            bad_error: bad_error,
+            bad: false,
+        }
+    }
+
+    /// Long run bit error rate in the stationary state distribution.
+    pub fn average_error_rate(&self) -> f64 {
+        let transitions = self.p_good_to_bad + self.p_bad_to_good;
+        if transitions == 0.0 {
+            return self.good_error;
+        }
+        (self.p_bad_to_good * self.good_error + self.p_good_to_bad * self.bad_error) / transitions
+    }
+}
+
+impl Channel for GilbertElliottChannel {
+    fn transmit(&mut self, word: &BitVec, rng: &mut dyn RngCore) -> BitVec {
+        word.iter()
+            .by_vals()
+            .map(|bit| {
+                let switch = if self.bad {
+                    self.p_bad_to_good
+                } else {
+                    self.p_good_to_bad
+                };
+                if rng.gen_bool(switch) {
+                    self.bad = !self.bad;
+                }
+                let error = if self.bad {
+                    self.bad_error
+                } else {
+                    self.good_error
+                };
+                bit ^ rng.gen_bool(error)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn count_flips(sent: &BitVec, received: &BitVec) -> usize {
+        sent.iter()
+            .zip(received.iter())
+            .filter(|(s, r)| s != r)
+            .count()
+    }
+
+    #[test]
+    fn binary_symmetric_channel_test() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let word = bitvec![0; 10000];
+
+        let received = BinarySymmetricChannel::new(0.0).transmit(&word, &mut rng);
+        assert_eq!(received, word);
+
+        let received = BinarySymmetricChannel::new(0.1).transmit(&word, &mut rng);
+        let flips = count_flips(&word, &received);
+        assert!(flips > 900 && flips < 1100, "flips: {}", flips);
+    }
+
+    #[test]
+    fn fixed_errors_channel_test() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let word = bitvec![1, 0, 1, 1, 0, 0, 1, 0, 1, 1, 1, 0, 0, 1, 0];
+        let mut channel = FixedErrorsChannel::new(3);
+        for _ in 0..100 {
+            let received = channel.transmit(&word, &mut rng);
+            assert_eq!(count_flips(&word, &received), 3);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_errors_test() {
+        let word = bitvec![0; 255];
+        let mut channel = BinarySymmetricChannel::new(0.05);
+        let first = channel.transmit(&word, &mut StdRng::seed_from_u64(7));
+        let second = channel.transmit(&word, &mut StdRng::seed_from_u64(7));
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn awgn_bpsk_channel_llrs_test() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let word = bitvec![1; 20000];
+        // rate 1/2 at 3 dB: variance 1 / (2 * 0.5 * 1.995), mean LLR -2 / variance
+        let mut channel = AwgnBpskChannel::new(3.0, 0.5);
+        let llrs = channel.llrs(&word, &mut rng);
+        let mean = llrs.iter().sum::<f64>() / llrs.len() as f64;
+        let expected = -2.0 * 10f64.powf(0.3);
+        assert!((mean - expected).abs() < 0.1, "mean: {}", mean);
+
+        let errors = channel
+            .transmit(&word, &mut rng)
+            .iter()
+            .by_vals()
+            .filter(|bit| !bit)
+            .count();
+        // Q(sqrt(2 * 0.5 * 1.995)) = 0.0794
+        let rate = errors as f64 / word.len() as f64;
+        assert!((rate - 0.0794).abs() < 0.01, "rate: {}", rate);
+    }
+
+    #[test]
+    fn awgn_bpsk_channel_high_snr_test() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let word = bitvec![1, 0, 1, 1, 0, 0, 1, 0];
+        let mut channel = AwgnBpskChannel::new(30.0, 1.0);
+        assert_eq!(channel.transmit(&word, &mut rng), word);
+    }
+
+    #[test]
+    fn burst_channel_test() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let word = bitvec![0; 63];
+        let mut channel = BurstChannel::new(6);
+        for _ in 0..100 {
+            let received = channel.transmit(&word, &mut rng);
+            let first = received.iter().by_vals().position(|bit| bit).unwrap();
+            let last = 62 - received.iter().by_vals().rev().position(|bit| bit).unwrap();
+            assert_eq!(last - first + 1, 6);
+        }
+        assert_eq!(
+            BurstChannel::new(1).transmit(&word, &mut rng).count_ones(),
+            1
+        );
+    }
+
+    #[test]
+    fn gilbert_elliott_channel_test() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let word = bitvec![0; 200000];
+        let mut channel = GilbertElliottChannel::new(0.01, 0.1, 0.001, 0.3);
+        let expected = (0.1 * 0.001 + 0.01 * 0.3) / 0.11;
+        assert!((channel.average_error_rate() - expected).abs() < 1e-12);
+
+        let received = channel.transmit(&word, &mut rng);
+        let rate = received.count_ones() as f64 / word.len() as f64;
+        assert!((rate - expected).abs() < 0.005, "rate: {}", rate);
+
+        // errors come in clusters, more adjacent pairs than independent ones
+        let pairs = received
+            .iter()
+            .by_vals()
+            .zip(received.iter().by_vals().skip(1))
+            .filter(|(a, b)| *a && *b)
+            .count() as f64;
+        assert!(pairs / word.len() as f64 > 2.0 * rate * rate);
+    }
+}
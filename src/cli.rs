@@ -0,0 +1,922 @@
+use crate::bch_code::BchCode;
+use crate::benchmark::{self, BenchmarkConfig, Operation};
+use crate::berlekamp_decoder::BerlekampDecoder;
+use crate::bounds::CodeBounds;
+use crate::channel::{BinarySymmetricChannel, BurstChannel, Channel, FixedErrorsChannel};
+use crate::common;
+use crate::container::{ContainerReader, ContainerWriter};
+use crate::decoder::Decoder;
+use crate::design;
+use crate::encoder::Encoder;
+use crate::framing;
+use crate::rng;
+use crate::simple_decoder::SimpleDecoder;
+use crate::simulation::{self, SimulationConfig};
+use crate::stream::{BchReader, BchWriter};
+use bitvec::prelude::*;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "Usage: bch <command> [options]
+
+Commands:
+  encode --m <m> --t <t> [--n <n>] [--prime-poly <poly>] [--first-root <b>]
+         --input <file> --output <file>
+      Encode a file in k-bit blocks, the last one zero padded, into a
+      container whose header describes the code and the payload length.
+      When --input is a pipe its length is unknown and --output has to be
+      seekable, the header is written again once the payload has been read.
+
+  decode [--decoder simple|berlekamp] --input <file> --output <file>
+      Decode a container written by encode. Exits with 1 when a block could
+      not be corrected, such blocks are written as received.
+
+  inject --channel fixed|bsc|burst (--errors <count> | --p <p> | --length <bits>)
+         --input <file> --output <file> [--log <file>] [--seed <seed>]
+      Corrupt the code words of a container: a fixed count of errors per
+      block, a BSC or one burst per block. The flipped positions are logged
+      to --log, by default the output path with .log appended.
+
+  simulate --m <m> --t <t> [--n <n>] [--prime-poly <poly>] [--first-root <b>]
+           [--decoder simple|berlekamp] --channel fixed|bsc|burst
+           (--errors <from:to:steps> | --p <from:to:steps> | --length <from:to:steps>)
+           [--max-frames <count>] [--frame-errors <count>] [--threads <count>]
+           [--seed <seed>] [--csv <file>] [--json <file>]
+      Monte Carlo frame and bit error rates. A point stops after --max-frames
+      frames (10000) or --frame-errors frame errors (100).
+
+  design --m <m> [--prime-poly <poly>] [--all-t]
+      List the primitive narrow-sense codes of length 2^m - 1 with their k,
+      t, rate, designed distance and generator polynomial in octal and hex.
+      With --all-t every t up to the largest is listed with its code, not
+      only the t at which the code changes.
+
+  bounds --m <m> --t <t> [--n <n>] [--prime-poly <poly>] [--first-root <b>]
+         (--p <from:to:steps> | --ebn0 <from:to:steps>)
+      Print analytical error rates on a BSC or on BPSK over AWGN.
+
+  bench [--m <m> --t <t> [--n <n>] [--prime-poly <poly>]]
+        [--operation encode|simple|berlekamp] [--min-time <seconds>] [--seed <seed>]
+      Measure encoding and decoding throughput at 0, t/2 and t errors, by
+      default on the m error correcting codes for m = 4..14, the simple
+      decoder on a single word per point above n = 1023.
+
+Polynomials are binary, highest degree first (100101), or hex (0x25).";
+
+/// Runs the command line, returns the process exit code.
+pub fn run(args: &[String], out: &mut dyn Write) -> Result<i32, String> {
+    let (command, rest) = match args.split_first() {
+        Some(split) => split,
+        None => return Err(USAGE.to_owned()),
+    };
+    let options = Options::parse(rest)?;
+    match command.as_str() {
+        "encode" => encode(&options, out),
+        "decode" => decode(&options, out),
+        "inject" => inject(&options, out),
+        "simulate" => simulate(&options, out),
+        "design" => design(&options, out),
+        "bounds" => bounds(&options, out),
+        "bench" => bench(&options, out),
+        "help" | "--help" => {
+            writeln!(out, "{}", USAGE).map_err(io_error)?;
+            Ok(0)
+        }
+        _ => Err(format!("Unknown command: {}\n\n{}", command, USAGE)),
+    }
+}
+
+/// `--key value` pairs and `--flag` switches.
+pub struct Options {
+    values: HashMap<String, String>,
+    flags: Vec<String>,
+}
+
+impl Options {
+    pub fn parse(args: &[String]) -> Result<Options, String> {
+        let mut values = HashMap::new();
+        let mut flags = Vec::new();
+        let mut args = args.iter().peekable();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                return Err(format!("Unexpected argument: {}", arg));
+            }
+            let key = arg.trim_start_matches("--").to_owned();
+            match args.peek() {
+                Some(value) if !value.starts_with("--") => {
+                    values.insert(key, args.next().unwrap().clone());
+                }
+                _ => flags.push(key),
+            }
+        }
+        Ok(Options {
+            values: values,
+            flags: flags,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(|value| value.as_str())
+    }
+
+    pub fn has_flag(&self, key: &str) -> bool {
+        self.flags.iter().any(|flag| flag == key)
+    }
+
+    pub fn parse_value<T: FromStr>(&self, key: &str) -> Result<Option<T>, String> {
+        match self.get(key) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("Invalid value of --{}: {}", key, value)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn required<T: FromStr>(&self, key: &str) -> Result<T, String> {
+        self.parse_value(key)?
+            .ok_or_else(|| format!("Missing option --{}", key))
+    }
+}
+
+/// Code given by --m and --t, optionally --n (shortened), --prime-poly and
+/// --first-root. k follows from the generator polynomial.
+pub fn code_from_options(options: &Options) -> Result<BchCode, String> {
+    let m: u32 = options.required("m")?;
+    let t: i32 = options.required("t")?;
+    let prime_poly = prime_poly_from_options(options, m)?;
+    let cycle_len = 2i32.pow(m) - 1;
+    let n = options.parse_value("n")?.unwrap_or(cycle_len);
+    if t < 1 || n < 1 || n > cycle_len {
+        return Err(format!("Bad code parameters. n: {}, t: {}", n, t));
+    }
+
+    let mut code = BchCode::new(n, 0, t, &prime_poly);
+    if let Some(first_root) = options.parse_value("first-root")? {
+        code = code.with_first_root(first_root);
+    }
+    code = code.with_derived_k();
+    if code.k <= 0 {
+        return Err(format!("No BCH code with n: {} and t: {}", n, t));
+    }
+    Ok(code)
+}
+
+/// Polynomial given by --prime-poly or the default one of degree m.
+pub fn prime_poly_from_options(options: &Options, m: u32) -> Result<BitVec, String> {
+    if m < common::MIN_M || m > common::MAX_M {
+        return Err(format!(
+            "m has to be between {} and {}, got {}",
+            common::MIN_M,
+            common::MAX_M,
+            m
+        ));
+    }
+    let prime_poly = match options.get("prime-poly") {
+        Some(poly) => parse_poly(poly)?,
+        None => common::get_primitive_poly(m).unwrap(),
+    };
+    if prime_poly.len() as u32 != m + 1 {
+        return Err(format!("Primitive polynomial has to be of degree {}", m));
+    }
+    if !common::is_primitive_poly(&prime_poly) {
+        return Err(format!(
+            "Polynomial 0x{} is not primitive",
+            design::poly_to_hex(&prime_poly)
+        ));
+    }
+    Ok(prime_poly)
+}
+
+/// Decoder named by --decoder, Berlekamp by default.
+pub fn decoder_from_options(
+    options: &Options,
+    code: &BchCode,
+) -> Result<Box<dyn Decoder + Send + Sync>, String> {
+    match options.get("decoder").unwrap_or("berlekamp") {
+        "berlekamp" => Ok(Box::new(BerlekampDecoder::from_code(code))),
+        "simple" => Ok(Box::new(SimpleDecoder::from_code(code))),
+        decoder => Err(format!("Unknown decoder: {}", decoder)),
+    }
+}
+
+/// Option holding the parameter of the channel named by --channel.
+pub fn channel_parameter_option(options: &Options) -> Result<&'static str, String> {
+    match options.required::<String>("channel")?.as_str() {
+        "fixed" => Ok("errors"),
+        "bsc" => Ok("p"),
+        "burst" => Ok("length"),
+        channel => Err(format!("Unknown channel: {}", channel)),
+    }
+}
+
+/// Channel named by --channel with the given parameter.
+pub fn make_channel(options: &Options, parameter: f64) -> Result<Box<dyn Channel>, String> {
+    let option = channel_parameter_option(options)?;
+    let channel: Box<dyn Channel> = match option {
+        "errors" | "length" => {
+            if !(parameter >= 0.0 && parameter.fract() == 0.0) {
+                return Err(format!("Invalid value of --{}: {}", option, parameter));
+            }
+            match option {
+                "errors" => Box::new(FixedErrorsChannel::new(parameter as usize)),
+                _ => Box::new(BurstChannel::new(parameter as usize)),
+            }
+        }
+        _ => {
+            if !(0.0..=1.0).contains(&parameter) {
+                return Err(format!("Invalid value of --p: {}", parameter));
+            }
+            Box::new(BinarySymmetricChannel::new(parameter))
+        }
+    };
+    Ok(channel)
+}
+
+/// Seed given by --seed, BCH_SEED or a random one.
+pub fn seed_from_options(options: &Options) -> Result<u64, String> {
+    match options.parse_value("seed")? {
+        Some(seed) => Ok(seed),
+        None => rng::seed_from_env(),
+    }
+}
+
+/// Binary digits, highest degree first, or hex with 0x prefix.
+pub fn parse_poly(poly: &str) -> Result<BitVec, String> {
+    let invalid = || format!("Invalid polynomial: {}", poly);
+    if poly.starts_with("0x") {
+        let value = u64::from_str_radix(&poly[2..], 16).map_err(|_| invalid())?;
+        if value == 0 {
+            return Err(invalid());
+        }
+        let degree = 63 - value.leading_zeros();
+        return Ok((0..(degree + 1))
+            .rev()
+            .map(|i| value & (1 << i) != 0)
+            .collect());
+    }
+    poly.chars()
+        .map(|digit| match digit {
+            '0' => Ok(false),
+            '1' => Ok(true),
+            _ => Err(invalid()),
+        })
+        .collect()
+}
+
+/// `from:to:steps` with evenly spaced points, ends included.
+pub fn parse_range(range: &str) -> Result<Vec<f64>, String> {
+    let invalid = || format!("Invalid range: {}, expected from:to:steps", range);
+    let parts: Vec<&str> = range.split(':').collect();
+    if parts.len() == 1 {
+        return Ok(vec![parts[0].parse().map_err(|_| invalid())?]);
+    }
+    if parts.len() != 3 {
+        return Err(invalid());
+    }
+    let from: f64 = parts[0].parse().map_err(|_| invalid())?;
+    let to: f64 = parts[1].parse().map_err(|_| invalid())?;
+    let steps: usize = parts[2].parse().map_err(|_| invalid())?;
+    match steps {
+        0 => Err(invalid()),
+        1 => Ok(vec![from]),
+        _ => Ok((0..steps)
+            .map(|i| from + (to - from) * i as f64 / (steps - 1) as f64)
+            .collect()),
+    }
+}
+
+pub fn io_error(err: std::io::Error) -> String {
+    format!("I/O error: {}", err)
+}
+
+fn format_figure(figure: Option<f64>) -> String {
+    match figure {
+        Some(figure) => format!("{:.4e}", figure),
+        None => "-".to_owned(),
+    }
+}
+
+/// Opens --input with its length, None when it is not a regular file such as
+/// a pipe.
+fn open_input(options: &Options) -> Result<(BufReader<File>, Option<u64>), String> {
+    let path: String = options.required("input")?;
+    let file = File::open(&path).map_err(|err| format!("Cannot read {}: {}", path, err))?;
+    let metadata = file
+        .metadata()
+        .map_err(|err| format!("Cannot read {}: {}", path, err))?;
+    let len = if metadata.is_file() {
+        Some(metadata.len())
+    } else {
+        None
+    };
+    Ok((BufReader::new(file), len))
+}
+
+fn create_output(options: &Options) -> Result<BufWriter<File>, String> {
+    let path: String = options.required("output")?;
+    let file = File::create(&path).map_err(|err| format!("Cannot write {}: {}", path, err))?;
+    Ok(BufWriter::new(file))
+}
+
+fn read_input(options: &Options) -> Result<Vec<u8>, String> {
+    let path: String = options.required("input")?;
+    fs::read(&path).map_err(|err| format!("Cannot read {}: {}", path, err))
+}
+
+fn write_output(options: &Options, data: &[u8]) -> Result<(), String> {
+    let path: String = options.required("output")?;
+    fs::write(&path, data).map_err(|err| format!("Cannot write {}: {}", path, err))
+}
+
+fn encode(options: &Options, out: &mut dyn Write) -> Result<i32, String> {
+    let code = code_from_options(options)?;
+    let (mut input, payload_len) = open_input(options)?;
+    let output = create_output(options)?;
+    let payload_len = match payload_len {
+        Some(payload_len) => {
+            let mut writer = BchWriter::new(output, &code, payload_len)?;
+            io::copy(&mut input, &mut writer).map_err(io_error)?;
+            writer.finish().map_err(io_error)?;
+            payload_len
+        }
+        None => {
+            let mut writer = BchWriter::with_unknown_len(output, &code)?;
+            let payload_len = io::copy(&mut input, &mut writer).map_err(io_error)?;
+            writer.finish_seek().map_err(|err| {
+                format!(
+                    "Cannot write a payload of unknown length, --output has to be seekable: {}",
+                    err
+                )
+            })?;
+            payload_len
+        }
+    };
+    writeln!(
+        out,
+        "BCH({}, {}) t = {}: {} blocks",
+        code.n,
+        code.k,
+        code.t,
+        framing::block_count(code.k, payload_len)
+    )
+    .map_err(io_error)?;
+    Ok(0)
+}
+
+fn decode(options: &Options, out: &mut dyn Write) -> Result<i32, String> {
+    let (input, _) = open_input(options)?;
+    let reader = BchReader::new(input)?;
+    let decoder = decoder_from_options(options, &reader.header().code)?;
+    let mut reader = reader.with_decoder(decoder);
+    let mut output = create_output(options)?;
+    io::copy(&mut reader, &mut output).map_err(io_error)?;
+    output.flush().map_err(io_error)?;
+    let stats = reader.stats();
+    writeln!(
+        out,
+        "blocks: {}, corrected bits: {}, uncorrectable blocks: {}",
+        stats.blocks,
+        stats.corrected_bits,
+        stats.uncorrectable_blocks.len()
+    )
+    .map_err(io_error)?;
+    if !stats.uncorrectable_blocks.is_empty() {
+        writeln!(out, "uncorrectable: {:?}", stats.uncorrectable_blocks).map_err(io_error)?;
+        return Ok(1);
+    }
+    Ok(0)
+}
+
+fn inject(options: &Options, out: &mut dyn Write) -> Result<i32, String> {
+    let parameter: f64 = options.required(channel_parameter_option(options)?)?;
+    let mut channel = make_channel(options, parameter)?;
+    let seed = seed_from_options(options)?;
+    let data = read_input(options)?;
+
+    let mut reader = ContainerReader::new(&data[..])?;
+    let header = reader.header().clone();
+    let words = reader.read_words()?;
+    let (received, flips) =
+        framing::transmit_words(&words, channel.as_mut(), &mut rng::seeded_rng(seed));
+    let mut writer = ContainerWriter::new(Vec::new(), &header.code)?;
+    writer.write_words(header.payload_len, &received)?;
+    write_output(options, &writer.into_inner())?;
+
+    let log_path = match options.get("log") {
+        Some(path) => path.to_owned(),
+        None => format!("{}.log", options.required::<String>("output")?),
+    };
+    let mut log = format!(
+        "# seed = {} channel = {} {} = {}\nblock\tbit\tfile_bit\n",
+        seed,
+        options.required::<String>("channel")?,
+        channel_parameter_option(options)?,
+        parameter
+    );
+    for (block, bit) in &flips {
+        log += &format!("{}\t{}\t{}\n", block, bit, header.bit_offset(*block, *bit));
+    }
+    fs::write(&log_path, log).map_err(|err| format!("Cannot write {}: {}", log_path, err))?;
+
+    writeln!(out, "flipped bits: {}, seed: {}", flips.len(), seed).map_err(io_error)?;
+    Ok(0)
+}
+
+fn simulate(options: &Options, out: &mut dyn Write) -> Result<i32, String> {
+    let code = code_from_options(options)?;
+    let decoder = decoder_from_options(options, &code)?;
+    let parameter_option = channel_parameter_option(options)?;
+    let parameters = parse_range(&options.required::<String>(parameter_option)?)?;
+    for parameter in &parameters {
+        make_channel(options, *parameter)?;
+    }
+    let config = SimulationConfig::new(
+        options.parse_value("max-frames")?.unwrap_or(10000),
+        options.parse_value("frame-errors")?.unwrap_or(100),
+        seed_from_options(options)?,
+    );
+    let threads = match options.parse_value("threads")? {
+        Some(threads) => threads,
+        None => thread::available_parallelism().map_or(1, |threads| threads.get()),
+    };
+
+    let results = simulation::simulate_curve_parallel(
+        &Encoder::from_code(&code),
+        decoder.as_ref(),
+        code.k as usize,
+        &parameters,
+        |parameter| make_channel(options, parameter).unwrap(),
+        &config,
+        threads,
+    );
+
+    writeln!(
+        out,
+        "# BCH({}, {}) t = {} seed = {}",
+        code.n, code.k, code.t, config.seed
+    )
+    .map_err(io_error)?;
+    writeln!(
+        out,
+        "{}\tframes\tframe_errors\tfailures\tfer\tber",
+        parameter_option
+    )
+    .map_err(io_error)?;
+    for result in &results {
+        writeln!(
+            out,
+            "{:.4e}\t{}\t{}\t{}\t{:.4e}\t{:.4e}",
+            result.parameter,
+            result.frames,
+            result.frame_errors,
+            result.decoder_failures,
+            result.fer(),
+            result.ber()
+        )
+        .map_err(io_error)?;
+    }
+
+    if let Some(path) = options.get("csv") {
+        fs::write(path, simulation::to_csv(&results))
+            .map_err(|err| format!("Cannot write {}: {}", path, err))?;
+    }
+    if let Some(path) = options.get("json") {
+        fs::write(path, simulation::to_json(&results))
+            .map_err(|err| format!("Cannot write {}: {}", path, err))?;
+    }
+    Ok(0)
+}
+
+fn design(options: &Options, out: &mut dyn Write) -> Result<i32, String> {
+    let m: u32 = options.required("m")?;
+    let prime_poly = prime_poly_from_options(options, m)?;
+    writeln!(
+        out,
+        "# m = {} prime poly = 0x{}",
+        m,
+        design::poly_to_hex(&prime_poly)
+    )
+    .map_err(io_error)?;
+    writeln!(out, "n\tk\tt\td\trate\tgen_octal\tgen_hex").map_err(io_error)?;
+    let codes = if options.has_flag("all-t") {
+        design::codes_for_every_t(&prime_poly)
+    } else {
+        design::primitive_codes(&prime_poly)
+    };
+    for code in codes {
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{:.4}\t{}\t{}",
+            code.n,
+            code.k,
+            code.t,
+            code.designed_distance(),
+            code.rate(),
+            design::poly_to_octal(&code.gen_poly),
+            design::poly_to_hex(&code.gen_poly)
+        )
+        .map_err(io_error)?;
+    }
+    Ok(0)
+}
+
+fn bounds(options: &Options, out: &mut dyn Write) -> Result<i32, String> {
+    let code = code_from_options(options)?;
+    let bounds = CodeBounds::new(&code);
+    writeln!(
+        out,
+        "# BCH({}, {}) t = {} rate = {:.4} d {}",
+        code.n,
+        code.k,
+        code.t,
+        bounds.rate(),
+        match bounds.weights.as_ref().and_then(|w| w.min_distance()) {
+            Some(d) => format!("= {}", d),
+            None => format!(">= {}", 2 * code.t + 1),
+        }
+    )
+    .map_err(io_error)?;
+
+    if let Some(range) = options.get("p") {
+        writeln!(out, "p\tfer\tber\tundetected").map_err(io_error)?;
+        for p in parse_range(range)? {
+            writeln!(
+                out,
+                "{:.4e}\t{:.4e}\t{:.4e}\t{}",
+                p,
+                bounds.bsc_block_error_rate(p),
+                bounds.bsc_bit_error_rate(p),
+                format_figure(bounds.bsc_undetected_error_rate(p))
+            )
+            .map_err(io_error)?;
+        }
+    } else if let Some(range) = options.get("ebn0") {
+        writeln!(out, "ebn0_db\tp_hard\tfer_hard\tfer_union\tber_union").map_err(io_error)?;
+        for eb_n0_db in parse_range(range)? {
+            writeln!(
+                out,
+                "{:.2}\t{:.4e}\t{:.4e}\t{}\t{}",
+                eb_n0_db,
+                bounds.awgn_crossover(eb_n0_db),
+                bounds.awgn_hard_block_error_rate(eb_n0_db),
+                format_figure(bounds.awgn_union_block_error_rate(eb_n0_db)),
+                format_figure(bounds.awgn_union_bit_error_rate(eb_n0_db))
+            )
+            .map_err(io_error)?;
+        }
+    } else {
+        return Err("Missing option --p or --ebn0".to_owned());
+    }
+    Ok(0)
+}
+
+fn bench(options: &Options, out: &mut dyn Write) -> Result<i32, String> {
+    let default_suite = options.get("m").is_none();
+    let codes = if default_suite {
+        benchmark::default_codes()
+    } else {
+        vec![code_from_options(options)?]
+    };
+    let operations = match options.get("operation") {
+        Some(name) => vec![Operation::parse(name)?],
+        None => Operation::all(),
+    };
+    let min_time: f64 = options.parse_value("min-time")?.unwrap_or(1.0);
+    let seed = seed_from_options(options)?;
+    let config = BenchmarkConfig::new(Duration::from_millis((min_time * 1000.0) as u64), 1, seed);
+
+    writeln!(out, "# seed = {}", seed).map_err(io_error)?;
+    writeln!(out, "n\tk\tt\toperation\terrors\twords\tfailures\tmbit_s").map_err(io_error)?;
+    for code in codes {
+        for operation in &operations {
+            let config = if default_suite {
+                benchmark::default_config(&code, *operation, &config)
+            } else {
+                config.clone()
+            };
+            for result in benchmark::benchmark_code(&code, &[*operation], &config) {
+                writeln!(
+                    out,
+                    "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:.3}",
+                    result.n,
+                    result.k,
+                    result.t,
+                    result.operation.name(),
+                    result.errors,
+                    result.words,
+                    result.failures,
+                    result.mbit_per_s()
+                )
+                .map_err(io_error)?;
+            }
+        }
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::HEADER_LEN;
+
+    fn to_args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(|arg| arg.to_owned()).collect()
+    }
+
+    fn run_to_string(line: &str) -> Result<String, String> {
+        let mut out = Vec::new();
+        run(&to_args(line), &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn options_parse_test() {
+        let options = Options::parse(&to_args("--m 5 --json --ebn0 -2:4:4")).unwrap();
+        assert_eq!(options.get("m"), Some("5"));
+        assert_eq!(options.get("ebn0"), Some("-2:4:4"));
+        assert!(options.has_flag("json"));
+        assert_eq!(options.required::<u32>("m"), Ok(5));
+        assert!(options.required::<u32>("t").is_err());
+        assert!(options.parse_value::<u32>("ebn0").is_err());
+        assert!(Options::parse(&to_args("m 5")).is_err());
+    }
+
+    #[test]
+    fn parse_poly_test() {
+        assert_eq!(parse_poly("100101"), Ok(bitvec![1, 0, 0, 1, 0, 1]));
+        assert_eq!(parse_poly("0x25"), Ok(bitvec![1, 0, 0, 1, 0, 1]));
+        assert!(parse_poly("102").is_err());
+        assert!(parse_poly("0x0").is_err());
+    }
+
+    #[test]
+    fn parse_range_test() {
+        assert_eq!(parse_range("0:1:5"), Ok(vec![0.0, 0.25, 0.5, 0.75, 1.0]));
+        assert_eq!(parse_range("0.01"), Ok(vec![0.01]));
+        assert!(parse_range("0:1").is_err());
+        assert!(parse_range("0:1:0").is_err());
+    }
+
+    #[test]
+    fn code_from_options_test() {
+        let code = code_from_options(&Options::parse(&to_args("--m 5 --t 3")).unwrap()).unwrap();
+        assert_eq!((code.n, code.k, code.t), (31, 16, 3));
+
+        let options = Options::parse(&to_args("--m 5 --t 3 --n 25 --prime-poly 0x25")).unwrap();
+        let code = code_from_options(&options).unwrap();
+        assert_eq!((code.n, code.k), (25, 10));
+
+        let options = Options::parse(&to_args("--m 4 --t 8")).unwrap();
+        assert!(code_from_options(&options).is_err());
+        for line in &[
+            "--m 40 --t 2 --prime-poly 0x10000000001",
+            "--m 5 --t 2 --prime-poly 0x21",
+            "--m 4 --t 2 --prime-poly 11111",
+        ] {
+            let options = Options::parse(&to_args(line)).unwrap();
+            assert!(code_from_options(&options).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn bounds_command_test() {
+        let result = run_to_string("bounds --m 3 --t 1 --p 0:0.5:2").unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines[0], "# BCH(7, 4) t = 1 rate = 0.5714 d = 3");
+        assert_eq!(lines[1], "p\tfer\tber\tundetected");
+        assert_eq!(lines[2], "0.0000e0\t0.0000e0\t0.0000e0\t0.0000e0");
+        assert_eq!(lines.len(), 4);
+
+        let result = run_to_string("bounds --m 4 --t 2 --ebn0 0:6:3").unwrap();
+        assert_eq!(result.lines().count(), 5);
+
+        assert!(run_to_string("bounds --m 4 --t 2").is_err());
+        assert!(run_to_string("unknown").is_err());
+    }
+
+    #[test]
+    fn encode_decode_commands_test() {
+        let dir = std::env::temp_dir().join(format!("bch_cli_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input");
+        let encoded = dir.join("encoded");
+        let decoded = dir.join("decoded");
+        let data: Vec<u8> = (0..100).collect();
+        fs::write(&input, &data).unwrap();
+        let files = |from: &std::path::Path, to: &std::path::Path| {
+            format!("--input {} --output {}", from.display(), to.display())
+        };
+
+        let result = run_to_string(&format!("encode --m 5 --t 3 {}", files(&input, &encoded)));
+        assert_eq!(result.unwrap(), "BCH(31, 16) t = 3: 50 blocks\n");
+
+        let mut corrupted = fs::read(&encoded).unwrap();
+        corrupted[HEADER_LEN + 10] ^= 0b0001_0001;
+        fs::write(&encoded, &corrupted).unwrap();
+        for decoder in &["simple", "berlekamp"] {
+            let line = format!("decode --decoder {} {}", decoder, files(&encoded, &decoded));
+            let mut out = Vec::new();
+            assert_eq!(run(&to_args(&line), &mut out), Ok(0));
+            assert_eq!(
+                String::from_utf8(out).unwrap(),
+                "blocks: 50, corrected bits: 2, uncorrectable blocks: 0\n"
+            );
+            assert_eq!(fs::read(&decoded).unwrap(), data);
+        }
+
+        // five errors in the first block are beyond t
+        corrupted[HEADER_LEN] ^= 0b1011_1010;
+        fs::write(&encoded, &corrupted).unwrap();
+        let line = format!("decode {}", files(&encoded, &decoded));
+        assert_eq!(run(&to_args(&line), &mut Vec::new()), Ok(1));
+        let line = format!("decode --decoder fast {}", files(&encoded, &decoded));
+        assert!(run_to_string(&line).is_err());
+        // the input file is not a container
+        let line = format!("decode {}", files(&input, &decoded));
+        assert!(run_to_string(&line).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn encode_pipe_command_test() {
+        let dir = std::env::temp_dir().join(format!("bch_pipe_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let fifo = dir.join("fifo");
+        let encoded = dir.join("encoded");
+        let decoded = dir.join("decoded");
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let data: Vec<u8> = (0..100).map(|i| (i * 7) as u8).collect();
+        let writer = {
+            let (fifo, data) = (fifo.clone(), data.clone());
+            thread::spawn(move || fs::write(&fifo, &data).unwrap())
+        };
+
+        let line = format!(
+            "encode --m 5 --t 3 --input {} --output {}",
+            fifo.display(),
+            encoded.display()
+        );
+        assert_eq!(
+            run_to_string(&line).unwrap(),
+            "BCH(31, 16) t = 3: 50 blocks\n"
+        );
+        writer.join().unwrap();
+        let line = format!(
+            "decode --input {} --output {}",
+            encoded.display(),
+            decoded.display()
+        );
+        run_to_string(&line).unwrap();
+        assert_eq!(fs::read(&decoded).unwrap(), data);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn inject_command_test() {
+        let dir = std::env::temp_dir().join(format!("bch_inject_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name).display().to_string();
+        fs::write(path("input"), &[7u8; 40]).unwrap();
+        let line = format!(
+            "encode --m 5 --t 3 --input {} --output {}",
+            path("input"),
+            path("encoded")
+        );
+        run_to_string(&line).unwrap();
+
+        let line = format!(
+            "inject --channel fixed --errors 2 --seed 5 --input {} --output {}",
+            path("encoded"),
+            path("corrupted")
+        );
+        assert_eq!(run_to_string(&line).unwrap(), "flipped bits: 40, seed: 5\n");
+        let log = fs::read_to_string(path("corrupted.log")).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines[0], "# seed = 5 channel = fixed errors = 2");
+        assert_eq!(lines.len(), 2 + 40);
+        // same seed, same errors
+        run_to_string(&line.replace("corrupted", "again")).unwrap();
+        assert_eq!(
+            fs::read(path("corrupted")).unwrap(),
+            fs::read(path("again")).unwrap()
+        );
+
+        let line = format!(
+            "decode --input {} --output {}",
+            path("corrupted"),
+            path("decoded")
+        );
+        assert_eq!(
+            run_to_string(&line).unwrap(),
+            "blocks: 20, corrected bits: 40, uncorrectable blocks: 0\n"
+        );
+        assert_eq!(fs::read(path("decoded")).unwrap(), vec![7u8; 40]);
+
+        let line = format!(
+            "inject --channel burst --length 4 --input {} --output {} --log {}",
+            path("encoded"),
+            path("burst"),
+            path("burst_flips")
+        );
+        run_to_string(&line).unwrap();
+        assert!(fs::read_to_string(path("burst_flips"))
+            .unwrap()
+            .contains("length = 4"));
+
+        let line = format!(
+            "inject --channel bsc --input {} --output {}",
+            path("encoded"),
+            path("bsc")
+        );
+        assert!(run_to_string(&line).is_err());
+        assert!(run_to_string(&(line.clone() + " --p 1.5")).is_err());
+        assert!(run_to_string(&(line.clone() + " --p NaN")).is_err());
+        assert!(run_to_string(&line.replace("bsc", "awgn")).is_err());
+
+        let line = format!(
+            "inject --input {} --output {}",
+            path("encoded"),
+            path("invalid")
+        );
+        assert!(run_to_string(&(line.clone() + " --channel fixed --errors -3")).is_err());
+        assert!(run_to_string(&(line.clone() + " --channel burst --length 2.7")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn simulate_command_test() {
+        let dir = std::env::temp_dir().join(format!("bch_simulate_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let csv = dir.join("curve.csv");
+        let json = dir.join("curve.json");
+        let line = format!(
+            "simulate --m 5 --t 3 --channel fixed --errors 0:4:5 --max-frames 50 --seed 9 \
+             --threads 2 --csv {} --json {}",
+            csv.display(),
+            json.display()
+        );
+        let result = run_to_string(&line).unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines[0], "# BCH(31, 16) t = 3 seed = 9");
+        assert_eq!(lines[1], "errors\tframes\tframe_errors\tfailures\tfer\tber");
+        assert_eq!(lines.len(), 7);
+        for line in &lines[2..6] {
+            assert!(line.contains("\t50\t0\t0\t0.0000e0\t0.0000e0"), "{}", line);
+        }
+        assert!(lines[6].starts_with("4.0000e0\t"));
+        assert_eq!(fs::read_to_string(&csv).unwrap().lines().count(), 6);
+        assert!(fs::read_to_string(&json)
+            .unwrap()
+            .starts_with("[\n  {\"parameter\": 0,"));
+
+        // same seed, same table whatever the number of threads
+        let bsc = "simulate --m 4 --t 2 --channel bsc --p 0.01:0.1:3 --max-frames 200 --seed 4";
+        let one = run_to_string(&format!("{} --threads 1", bsc)).unwrap();
+        assert_eq!(run_to_string(&format!("{} --threads 3", bsc)).unwrap(), one);
+
+        assert!(run_to_string("simulate --m 4 --t 2 --channel bsc --p 0:2:3").is_err());
+        assert!(run_to_string("simulate --m 4 --t 2 --channel bsc").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn design_command_test() {
+        let result = run_to_string("design --m 4").unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines[0], "# m = 4 prime poly = 0x13");
+        assert_eq!(lines[3], "15\t7\t2\t5\t0.4667\t721\t1d1");
+        assert_eq!(lines.len(), 6);
+
+        let result = run_to_string("design --m 4 --all-t").unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines[5], "15\t1\t4\t9\t0.0667\t77777\t7fff");
+        assert_eq!(lines.len(), 9);
+
+        assert!(run_to_string("design --m 4 --prime-poly 0x25").is_err());
+    }
+
+    #[test]
+    fn bench_command_test() {
+        let result =
+            run_to_string("bench --m 4 --t 2 --operation berlekamp --min-time 0 --seed 3").unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines[0], "# seed = 3");
+        assert_eq!(lines.len(), 5);
+        assert!(lines[4].starts_with("15\t7\t2\tberlekamp\t2\t1\t0\t"));
+
+        assert!(run_to_string("bench --m 4 --t 2 --operation fast").is_err());
+    }
+}
@@ -1,17 +1,18 @@
 use crate::bch_bitvec::*;
-use bitvec::*;
+use crate::galois_field::GaloisField;
+use bitvec::prelude::*;
 use itertools::Itertools;
 use primes::{is_prime, PrimeSet};
-use std::collections::VecDeque;
 use rand::Rng;
 
-pub fn get_random_places(n: i32, modulo: i32) ->Vec<usize> {
+/// n distinct places in 0..modulo, drawn from the given rng.
+pub fn get_random_places<R: Rng + ?Sized>(n: i32, modulo: i32, rng: &mut R) -> Vec<usize> {
     let mut nums = Vec::new();
-    for i in 0..n {
+    for _ in 0..n {
         loop {
-            let num = rand::thread_rng().gen_range(0, modulo) as usize;
+            let num = rng.gen_range(0, modulo) as usize;
             if !nums.contains(&num) {
-                nums.push(num); 
+                nums.push(num);
                 break;
             }
         }
@@ -19,65 +20,25 @@ pub fn get_random_places(n: i32, modulo: i32) ->Vec<usize> {
     nums
 }
 
-fn calculate_alphas(prime_polynomial: &BitVec) -> Vec<BitVec> {
-    let mut alphas: Vec<BitVec> = Vec::new();
-    let pol_size = prime_polynomial.len();
-    let alphas_count = 2u32.pow(pol_size as u32 - 1);
-
-    // push alpha 0
-    let mut alpha_0 = bitvec![0; pol_size];
-    alpha_0.set(pol_size - 1, true);
-    alphas.push(alpha_0);
-
-    // push rest
-    for i in 1..(alphas_count as usize) {
-        let mut alpha_i = alphas.get(i - 1).unwrap().clone();
-        alpha_i <<= 1;
-        alpha_i.push(false);
-        if alpha_i.get(0) == true {
-            alpha_i ^= (*prime_polynomial).clone();
-        }
-        alphas.push(alpha_i);
-    }
-    alphas
-}
-
-fn create_adding_table(alphas: &Vec<BitVec>) -> Vec<Vec<i32>> {
-    //TODO to doc: organization of table
-    let mut adding_table: Vec<Vec<i32>> = Vec::new();
-    let alphas_size: usize = alphas.len();
-
-    for row in 0..alphas_size {
-        let mut row_vec: Vec<i32> = Vec::with_capacity(alphas_size);
-
-        for col in 0..alphas_size {
-            let alpha_row = alphas.get(row).unwrap();
-            let alpha_col = alphas.get(col).unwrap();
-            let alphas_xor = (*alpha_row).clone() ^ (*alpha_col).clone();
-            let result_alpha_idx = alphas.iter().position(|alpha| alpha.eq(&alphas_xor));
-            match result_alpha_idx {
-                Some(idx) => row_vec.push(idx as i32),
-                None => row_vec.push(-1),
-            }
-        }
-
-        adding_table.push(row_vec);
-    }
-
-    adding_table
+pub fn get_random_msg<R: Rng + ?Sized>(k: usize, rng: &mut R) -> BitVec {
+    (0..k).map(|_| rng.gen::<bool>()).collect()
 }
 
-fn create_gen_pol(degree: u32, t: u32, adding_table: &Vec<Vec<i32>>) -> BitVec {
+fn create_cyclic_gen_pol(cycle_len: u32, t: u32, first_root: u32, field: &GaloisField) -> BitVec {
+    // roots are powers of beta = alpha^step, which has order cycle_len
+    let step = field.order() as u32 / cycle_len;
     let mut min_pols = Vec::new();
-    let layers: Vec<Vec<u32>> = get_n_disjunctive_layers(t, adding_table[0].len());
+    let layers: Vec<Vec<u32>> = get_consecutive_cosets(first_root, 2 * t, cycle_len);
     layers.iter().for_each(|layer| {
-        min_pols.push(calculate_layer_min_pol(layer, adding_table));
+        let alphas_layer = layer.iter().map(|beta| beta * step).collect();
+        min_pols.push(calculate_layer_min_pol(&alphas_layer, field));
     });
     finite_multiply_bitvecs_vec(&min_pols)
 }
 
-pub fn calculate_layer_min_pol(layer: &Vec<u32>, adding_table: &Vec<Vec<i32>>) -> BitVec {
+pub fn calculate_layer_min_pol(layer: &Vec<u32>, field: &GaloisField) -> BitVec {
     let layer_degree = layer.len() as u32;
+    let field_order = field.order() as u32;
     let mut min_pol = bitvec![0; (layer_degree + 1) as usize];
     min_pol.set(0, true);
     min_pol.set(layer_degree as usize, true);
@@ -85,11 +46,11 @@ pub fn calculate_layer_min_pol(layer: &Vec<u32>, adding_table: &Vec<Vec<i32>>) -
     for idx in 1..layer_degree {
         let combinations = layer.iter().combinations(idx as usize).collect_vec();
         let coefficient = combinations.iter().fold(-1, |sum, combination| {
-            let alpha = combination.iter().fold(0, |sum, &&alpha| {
-                (sum + alpha) % (2u32.pow(layer_degree) - 1)
-            });
+            let alpha = combination
+                .iter()
+                .fold(0, |sum, &&alpha| (sum + alpha) % field_order);
             if sum != -1i32 {
-                return adding_table[sum as usize][alpha as usize];
+                return field.add_alphas(sum, alpha as i32);
             } else {
                 return alpha as i32;
             }
@@ -102,11 +63,16 @@ pub fn calculate_layer_min_pol(layer: &Vec<u32>, adding_table: &Vec<Vec<i32>>) -
 }
 
 pub fn get_n_first_layers(n: u32, alphas_len: usize) -> Vec<Vec<u32>> {
+    get_layers_of(&(1..(n + 1)).collect(), alphas_len)
+}
+
+/// Layer (cyclotomic coset) of every given alpha power, duplicates included.
+pub fn get_layers_of(alphas: &Vec<u32>, alphas_len: usize) -> Vec<Vec<u32>> {
     let mut layers: Vec<Vec<u32>> = Vec::new();
 
-    for i in 1..(n + 1) {
+    for alpha in alphas {
         let mut layer = Vec::new();
-        layer.push(i);
+        layer.push(*alpha % (alphas_len - 1) as u32);
         loop {
             let candidate_alpha =
                 (*layer.iter_mut().last().unwrap() * 2) as u32 % (alphas_len - 1) as u32;
@@ -123,41 +89,106 @@ pub fn get_n_first_layers(n: u32, alphas_len: usize) -> Vec<Vec<u32>> {
     layers
 }
 
-fn get_n_disjunctive_layers(n: u32, alphas_len: usize) -> Vec<Vec<u32>> {
-    //TODO to doc: layers start with prime powers
-    let mut layers: Vec<Vec<u32>> = Vec::new();
+pub fn get_n_disjunctive_layers(n: u32, alphas_len: usize) -> Vec<Vec<u32>> {
+    get_n_disjunctive_cosets(n, alphas_len as u32 - 1)
+}
 
-    let mut start_numbers: VecDeque<u32> = VecDeque::new();
-    for i in 0..(alphas_len / 2) as usize {
-        start_numbers.push_back(1 + i as u32 * 2);
-    }
+/// Disjunctive cyclotomic cosets of 2 modulo `modulus` covering 1..=2n.
+pub fn get_n_disjunctive_cosets(n: u32, modulus: u32) -> Vec<Vec<u32>> {
+    get_consecutive_cosets(1, 2 * n, modulus)
+}
+
+/// Disjunctive cyclotomic cosets of 2 modulo `modulus` covering `count`
+/// consecutive powers starting at `first`, in order of their smallest power.
+pub fn get_consecutive_cosets(first: u32, count: u32, modulus: u32) -> Vec<Vec<u32>> {
+    get_consecutive_q_cosets(first, count, modulus, 2)
+}
 
-    for _i in 0..n {
-        if *start_numbers.front().unwrap() > 2 * n {
-            break;
+/// Same as `get_consecutive_cosets` for cyclotomic cosets of q, which give
+/// the minimal polynomials over GF(q).
+pub fn get_consecutive_q_cosets(first: u32, count: u32, modulus: u32, q: u32) -> Vec<Vec<u32>> {
+    let mut layers: Vec<Vec<u32>> = Vec::new();
+
+    for power in first..(first + count) {
+        let start = power % modulus;
+        if layers.iter().any(|layer| layer.contains(&start)) {
+            continue;
         }
         let mut layer: Vec<u32> = Vec::new();
-        layer.push(start_numbers.pop_front().unwrap() % (alphas_len as u32 - 1));
+        layer.push(start);
         loop {
-            let candidate = (layer.iter().last().unwrap() * 2) % (alphas_len as u32 - 1);
+            let candidate = (layer.iter().last().unwrap() * q) % modulus;
             if layer.contains(&candidate) {
                 layer.sort();
                 layers.push(layer);
                 break;
             } else {
                 layer.push(candidate);
-                start_numbers.retain(|n| *n != candidate);
             }
         }
     }
     layers
 }
 
+/// Range of m with a default primitive polynomial.
+pub const MIN_M: u32 = 2;
+pub const MAX_M: u32 = 16;
+
+/// Default primitive polynomial of degree m, MIN_M <= m <= MAX_M.
+pub fn get_primitive_poly(m: u32) -> Option<BitVec> {
+    let poly: u32 = match m {
+        2 => 0x7,
+        3 => 0xb,
+        4 => 0x13,
+        5 => 0x25,
+        6 => 0x43,
+        7 => 0x89,
+        8 => 0x11d,
+        9 => 0x211,
+        10 => 0x409,
+        11 => 0x805,
+        12 => 0x1053,
+        13 => 0x201b,
+        14 => 0x4443,
+        15 => 0x8003,
+        16 => 0x1100b,
+        _ => return None,
+    };
+    Some((0..(m + 1)).rev().map(|i| poly & (1 << i) != 0).collect())
+}
+
+/// Whether x has order 2^m - 1 modulo the polynomial of degree m, for
+/// MIN_M <= m <= MAX_M.
+pub fn is_primitive_poly(poly: &BitVec) -> bool {
+    let m = poly.len() as u32;
+    if m < MIN_M + 1 || m > MAX_M + 1 || !poly[0] || !poly[poly.len() - 1] {
+        return false;
+    }
+    let m = m - 1;
+    let value = poly
+        .iter()
+        .by_vals()
+        .fold(0u32, |value, bit| (value << 1) | bit as u32);
+    let order = (1u32 << m) - 1;
+    let mut power = 1u32;
+    for i in 1..(order + 1) {
+        power <<= 1;
+        if power >> m != 0 {
+            power ^= value;
+        }
+        if power == 1 {
+            return i == order;
+        }
+    }
+    false
+}
+
 pub fn validate_params(n: i32, k: i32, gen_poly: &BitVec, prime_poly: &BitVec) {
     if gen_poly.len() == 0
         // || n != k + gen_poly.len() as i32 - 1
         || gen_poly[0] == false
         || prime_poly[0] == false
+        || n > 2i32.pow(prime_poly.len() as u32 - 1) - 1
     {
         panic!(
             "Bad coder parameters. n: {}, k: {}, gen: {:?}",
@@ -166,32 +197,38 @@ pub fn validate_params(n: i32, k: i32, gen_poly: &BitVec, prime_poly: &BitVec) {
     }
 }
 
-pub fn get_gen_poly(degree: i32, t: i32, prime_poly: &BitVec) -> BitVec {
+/// Generator polynomial with 2t consecutive roots starting at alpha^first_root,
+/// first_root other than 1 gives a non-narrow-sense code.
+pub fn get_gen_poly(t: i32, first_root: i32, prime_poly: &BitVec) -> BitVec {
     //TODO move to classes
-    let alphas = calculate_alphas(&prime_poly);
-    let adding_table = create_adding_table(&alphas);
-    let gen_poly = create_gen_pol(degree as u32, t as u32, &adding_table);
+    let field = GaloisField::new(prime_poly);
+    let gen_poly = create_cyclic_gen_pol(field.order() as u32, t as u32, first_root as u32, &field);
     gen_poly
 }
 
-pub fn get_gen_poly_and_adding_table(
-    degree: i32,
+pub fn get_cyclic_gen_poly_and_field(
+    cycle_len: i32,
     t: i32,
+    first_root: i32,
     prime_poly: &BitVec,
-) -> (BitVec, Vec<Vec<i32>>) {
-    //TODO move to classes
-    let alphas = calculate_alphas(&prime_poly);
-    let adding_table = create_adding_table(&alphas);
-    let gen_poly = create_gen_pol(degree as u32, t as u32, &adding_table);
-    (gen_poly, adding_table)
+) -> (BitVec, GaloisField) {
+    let field = GaloisField::new(prime_poly);
+    let gen_poly = create_cyclic_gen_pol(cycle_len as u32, t as u32, first_root as u32, &field);
+    (gen_poly, field)
 }
 
 pub fn finite_multiply_bitvecs_vec(vec: &Vec<BitVec>) -> BitVec {
     vec.iter().fold(bitvec![1], |folded, pol| {
+        // shifts of the longer factor, one for every term of the shorter
+        let (shifted, terms) = if folded.len() >= pol.len() {
+            (&folded, pol)
+        } else {
+            (pol, &folded)
+        };
         let mut to_add: Vec<BitVec> = Vec::new();
-        for (i, bit) in pol.iter().rev().enumerate() {
+        for (i, bit) in terms.iter().rev().enumerate() {
             if bit == true {
-                let mut elem: BitVec = folded.clone();
+                let mut elem: BitVec = shifted.clone();
                 elem.extend(bitvec![0;i]);
                 to_add.push(elem);
             }
@@ -209,9 +246,8 @@ mod tests {
     #[test]
     fn get_gen_poly_test() {
         let prime_poly = bitvec![1, 0, 1, 1];
-        let degree = 3;
         let t = 2;
-        let result = get_gen_poly(degree, t, &prime_poly);
+        let result = get_gen_poly(t, 1, &prime_poly);
         let expected = bitvec![1, 1, 1, 1, 1, 1, 1];
         assert_eq!(result, expected);
     }
@@ -219,52 +255,70 @@ mod tests {
     #[test]
     fn get_gen_poly_test_2() {
         let prime_poly = bitvec![1, 0, 1, 1];
-        let degree = 3;
         let t = 1;
-        let result = get_gen_poly(degree, t, &prime_poly);
+        let result = get_gen_poly(t, 1, &prime_poly);
         let expected = bitvec![1, 0, 1, 1];
         assert_eq!(result, expected);
     }
 
     #[test]
-    fn create_gen_pol_test() {
-        let alphas = calculate_alphas(&bitvec![1, 0, 1, 1]);
-        let adding_table = create_adding_table(&alphas);
-        let expected = bitvec![1, 1, 1, 1, 1, 1, 1];
-        assert_eq!(expected, create_gen_pol(3, 2, &adding_table));
+    fn get_gen_poly_with_first_root_0_test() {
+        let prime_poly = bitvec![1, 0, 1, 1];
+        let result = get_gen_poly(1, 0, &prime_poly);
+        let expected = bitvec![1, 1, 1, 0, 1];
+        assert_eq!(result, expected);
     }
 
     #[test]
-    fn calculate_alphas_test() {
-        let alphas = calculate_alphas(&bitvec![1, 0, 1, 1]);
-        let expected = vec![
-            bitvec![0, 0, 0, 1],
-            bitvec![0, 0, 1, 0],
-            bitvec![0, 1, 0, 0],
-            bitvec![0, 0, 1, 1],
-            bitvec![0, 1, 1, 0],
-            bitvec![0, 1, 1, 1],
-            bitvec![0, 1, 0, 1],
-            bitvec![0, 0, 0, 1],
-        ];
-        assert_eq!(alphas, expected);
+    fn get_consecutive_cosets_test() {
+        let cosets = get_consecutive_cosets(0, 4, 15);
+        let expected = vec![vec![0], vec![1, 2, 4, 8], vec![3, 6, 9, 12]];
+        assert_eq!(cosets, expected);
+
+        let cosets = get_consecutive_cosets(2, 4, 15);
+        let expected = vec![vec![1, 2, 4, 8], vec![3, 6, 9, 12], vec![5, 10]];
+        assert_eq!(cosets, expected);
     }
 
     #[test]
-    fn create_adding_table_test() {
-        let alphas = calculate_alphas(&bitvec![1, 0, 1, 1]);
-        let result = create_adding_table(&alphas);
-        let expected = vec![
-            vec![-1, 3, 6, 1, 5, 4, 2, -1],
-            vec![3, -1, 4, 0, 2, 6, 5, 3],
-            vec![6, 4, -1, 5, 1, 3, 0, 6],
-            vec![1, 0, 5, -1, 6, 2, 4, 1],
-            vec![5, 2, 1, 6, -1, 0, 3, 5],
-            vec![4, 6, 3, 2, 0, -1, 1, 4],
-            vec![2, 5, 0, 4, 3, 1, -1, 2],
-            vec![-1, 3, 6, 1, 5, 4, 2, -1],
-        ];
-        assert_eq!(result, expected);
+    fn get_consecutive_q_cosets_test() {
+        let cosets = get_consecutive_q_cosets(1, 4, 15, 4);
+        let expected = vec![vec![1, 4], vec![2, 8], vec![3, 12]];
+        assert_eq!(cosets, expected);
+
+        let cosets = get_consecutive_q_cosets(5, 2, 63, 8);
+        let expected = vec![vec![5, 40], vec![6, 48]];
+        assert_eq!(cosets, expected);
+    }
+
+    #[test]
+    fn get_primitive_poly_test() {
+        assert_eq!(get_primitive_poly(4), Some(bitvec![1, 0, 0, 1, 1]));
+        assert_eq!(get_primitive_poly(17), None);
+        for m in 2..17 {
+            let field = GaloisField::new(&get_primitive_poly(m).unwrap());
+            assert!((1..(field.order() as u32 + 1)).all(|value| field.value_to_alpha(value) != -1));
+        }
+    }
+
+    #[test]
+    fn is_primitive_poly_test() {
+        for m in MIN_M..(MAX_M + 1) {
+            assert!(is_primitive_poly(&get_primitive_poly(m).unwrap()));
+        }
+        // x^4 + x^3 + x^2 + x + 1 is irreducible but x has order 5
+        assert!(!is_primitive_poly(&bitvec![1, 1, 1, 1, 1]));
+        assert!(!is_primitive_poly(&bitvec![1, 0, 0, 0, 0, 1]));
+        assert!(!is_primitive_poly(&bitvec![1, 0, 0, 1, 0, 0]));
+        assert!(!is_primitive_poly(&bitvec![1, 1]));
+        assert!(!is_primitive_poly(&bitvec![]));
+    }
+
+    #[test]
+    fn calculate_layer_min_pol_of_short_layer_test() {
+        let field = GaloisField::new(&bitvec![1, 0, 1, 1, 0, 0, 0, 1, 1]);
+        let result = calculate_layer_min_pol(&vec![17, 34, 68, 136], &field);
+        assert_eq!(result, bitvec![1, 0, 0, 1, 1]);
     }
 
     #[test]
@@ -288,6 +342,30 @@ mod tests {
         assert_eq!(layers, expected);
     }
 
+    #[test]
+    fn get_n_disjunctive_cosets_modulo_non_primitive_length_test() {
+        let cosets = get_n_disjunctive_cosets(2, 21);
+        let expected = vec![vec![1, 2, 4, 8, 11, 16], vec![3, 6, 12]];
+        assert_eq!(cosets, expected);
+
+        let cosets = get_n_disjunctive_cosets(2, 23);
+        let expected = vec![vec![1, 2, 3, 4, 6, 8, 9, 12, 13, 16, 18]];
+        assert_eq!(cosets, expected);
+    }
+
+    #[test]
+    fn create_cyclic_gen_pol_test() {
+        // (21, 12) code over GF(2^6), beta = alpha^3
+        let field = GaloisField::new(&bitvec![1, 0, 0, 0, 0, 1, 1]);
+        let result = create_cyclic_gen_pol(21, 2, 1, &field);
+        assert_eq!(result.len(), 10);
+
+        let mut x_n_plus_1 = bitvec![1; 1];
+        x_n_plus_1.extend(bitvec![0; 20]);
+        x_n_plus_1.push(true);
+        assert_eq!(x_n_plus_1.remainder_divide(&result).unwrap().any(), false);
+    }
+
     #[test]
     fn finite_multiply_bitvecs_test() {
         let to_multiply = vec![bitvec![1, 0, 1, 1, 0], bitvec![1, 1, 0, 1], bitvec![1, 1]];
@@ -0,0 +1,394 @@
+use crate::bch_code::BchCode;
+use crate::byte_codec;
+use crate::common;
+use crate::framing;
+use bitvec::prelude::*;
+use std::convert::{TryFrom, TryInto};
+use std::io::{Read, Write};
+
+pub const MAGIC: [u8; 4] = *b"BCHC";
+pub const VERSION: u8 = 1;
+
+/// Bytes of the header: magic, version, m, t (u16), n, k, primitive
+/// polynomial, first root (u32), payload length (u64) and the CRC-32 of the
+/// preceding bytes, all big endian.
+pub const HEADER_LEN: usize = 36;
+
+/// Self-describing header of an encoded stream. The code words of
+/// block_count() blocks follow it, packed back to back.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    pub code: BchCode,
+    pub payload_len: u64,
+}
+
+impl Header {
+    /// Only primitive codes, shortened or not, can be described.
+    pub fn new(code: &BchCode, payload_len: u64) -> Result<Header, String> {
+        if code.cycle_len != code.field_order() {
+            return Err("Container holds primitive or shortened codes only".to_owned());
+        }
+        if code.m() > common::MAX_M as i32 || code.t > std::u16::MAX as i32 || code.k <= 0 {
+            return Err(format!(
+                "Code cannot be stored. m: {}, t: {}, k: {}",
+                code.m(),
+                code.t,
+                code.k
+            ));
+        }
+        if container_bits(code, payload_len).is_none() {
+            return Err(format!(
+                "Payload of {} bytes does not fit in a container",
+                payload_len
+            ));
+        }
+        Ok(Header {
+            code: code.clone(),
+            payload_len: payload_len,
+        })
+    }
+
+    pub fn block_count(&self) -> usize {
+        framing::block_count(self.code.k, self.payload_len)
+    }
+
+    /// Bytes of the packed code words following the header.
+    pub fn words_len(&self) -> usize {
+        (self.block_count() * self.code.n as usize).div_ceil(8)
+    }
+
+    /// Offset of a code word bit from the start of the container.
+    pub fn bit_offset(&self, block: usize, bit: usize) -> usize {
+        HEADER_LEN * 8 + block * self.code.n as usize + bit
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let prime_poly = byte_codec::poly_to_u32(&self.code.prime_poly);
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.push(self.code.m() as u8);
+        bytes.extend(&(self.code.t as u16).to_be_bytes());
+        bytes.extend(&(self.code.n as u32).to_be_bytes());
+        bytes.extend(&(self.code.k as u32).to_be_bytes());
+        bytes.extend(&prime_poly.to_be_bytes());
+        bytes.extend(&(self.code.first_root as u32).to_be_bytes());
+        bytes.extend(&self.payload_len.to_be_bytes());
+        let checksum = crc32(&bytes);
+        bytes.extend(&checksum.to_be_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Header, String> {
+        if bytes.len() < HEADER_LEN {
+            return Err("Container header is too short!".to_owned());
+        }
+        if bytes[..4] != MAGIC {
+            return Err("Not a BCH container".to_owned());
+        }
+        if bytes[4] != VERSION {
+            return Err(format!("Unsupported container version: {}", bytes[4]));
+        }
+        let u32_at = |at: usize| u32::from_be_bytes(bytes[at..(at + 4)].try_into().unwrap());
+        if crc32(&bytes[..(HEADER_LEN - 4)]) != u32_at(HEADER_LEN - 4) {
+            return Err("Container header checksum mismatch".to_owned());
+        }
+
+        let m = bytes[5] as u32;
+        let t = u16::from_be_bytes(bytes[6..8].try_into().unwrap()) as i32;
+        let n = u32_at(8) as i64;
+        let k = u32_at(12) as i64;
+        let prime_poly = u32_at(16);
+        let first_root = u32_at(20) as i64;
+        let payload_len = u64::from_be_bytes(bytes[24..32].try_into().unwrap());
+        if m < common::MIN_M
+            || m > common::MAX_M
+            || prime_poly >> m != 1
+            || !common::is_primitive_poly(&byte_codec::poly_from_u32(prime_poly))
+        {
+            return Err(format!(
+                "Bad primitive polynomial in container header. m: {}, poly: {:#x}",
+                m, prime_poly
+            ));
+        }
+        let cycle_len = (1i64 << m) - 1;
+        if t < 1 || n < 1 || n > cycle_len || k < 1 || k > n || first_root > cycle_len {
+            return Err(format!(
+                "Bad code parameters in container header. n: {}, k: {}, t: {}",
+                n, k, t
+            ));
+        }
+
+        let prime_poly = byte_codec::poly_from_u32(prime_poly);
+        let code =
+            BchCode::new(n as i32, k as i32, t, &prime_poly).with_first_root(first_root as i32);
+        if code.clone().with_derived_k().k != code.k {
+            return Err(format!(
+                "Container header k: {} does not match the generator polynomial",
+                k
+            ));
+        }
+        Header::new(&code, payload_len)
+    }
+}
+
+/// Bits of the header and of the code words holding payload_len bytes, None
+/// when they overflow.
+fn container_bits(code: &BchCode, payload_len: u64) -> Option<usize> {
+    let blocks = payload_len.checked_mul(8)?.div_ceil(code.k as u64);
+    let bits = blocks
+        .checked_mul(code.n as u64)?
+        .checked_add(HEADER_LEN as u64 * 8)?;
+    usize::try_from(bits).ok()
+}
+
+/// CRC-32 as in zlib and PNG, reflected polynomial 0xEDB88320.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, byte| {
+        (0..8).fold(crc ^ *byte as u32, |crc, _| {
+            if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+fn io_error(err: std::io::Error) -> String {
+    format!("Container I/O error: {}", err)
+}
+
+/// Writes a header and already encoded blocks, stream::BchWriter encodes a
+/// payload on the fly.
+pub struct ContainerWriter<W: Write> {
+    out: W,
+    code: BchCode,
+}
+
+impl<W: Write> ContainerWriter<W> {
+    pub fn new(out: W, code: &BchCode) -> Result<ContainerWriter<W>, String> {
+        Header::new(code, 0)?;
+        Ok(ContainerWriter {
+            out: out,
+            code: code.clone(),
+        })
+    }
+
+    /// Writes already encoded blocks of a payload of payload_len bytes.
+    pub fn write_words(&mut self, payload_len: u64, words: &[BitVec]) -> Result<(), String> {
+        let header = Header::new(&self.code, payload_len)?;
+        if words.len() != header.block_count() {
+            return Err(format!(
+                "Expected {} code words, got {}",
+                header.block_count(),
+                words.len()
+            ));
+        }
+        self.out.write_all(&header.to_bytes()).map_err(io_error)?;
+        self.out
+            .write_all(&framing::pack_words(words))
+            .map_err(io_error)?;
+        self.out.flush().map_err(io_error)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Reads the header on construction, the code words on demand.
+/// stream::BchReader decodes them on the fly.
+pub struct ContainerReader<R: Read> {
+    input: R,
+    header: Header,
+}
+
+impl<R: Read> ContainerReader<R> {
+    pub fn new(mut input: R) -> Result<ContainerReader<R>, String> {
+        let mut bytes = [0u8; HEADER_LEN];
+        input.read_exact(&mut bytes).map_err(io_error)?;
+        Ok(ContainerReader {
+            input: input,
+            header: Header::from_bytes(&bytes)?,
+        })
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Reads the code words of the header. The buffer grows with the data
+    /// read, not with the length claimed by the header.
+    pub fn read_words(&mut self) -> Result<Vec<BitVec>, String> {
+        let words_len = self.header.words_len();
+        let mut bytes = Vec::new();
+        (&mut self.input)
+            .take(words_len as u64)
+            .read_to_end(&mut bytes)
+            .map_err(io_error)?;
+        if bytes.len() != words_len {
+            return Err(format!(
+                "Container is truncated, expected {} bytes of code words, got {}",
+                words_len,
+                bytes.len()
+            ));
+        }
+        framing::unpack_words(self.header.code.n, self.header.block_count(), &bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::berlekamp_decoder::BerlekampDecoder;
+    use crate::encoder::Encoder;
+    use crate::framing::DecodeStats;
+    use crate::stream::BchWriter;
+
+    fn get_code() -> BchCode {
+        BchCode::new(31, 16, 3, &bitvec![1, 0, 0, 1, 0, 1])
+    }
+
+    fn write_container(code: &BchCode, payload: &[u8]) -> Vec<u8> {
+        let mut writer = BchWriter::new(Vec::new(), code, payload.len() as u64).unwrap();
+        writer.write_all(payload).unwrap();
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn crc32_test() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn header_known_answer_test() {
+        let header = Header::new(&get_code(), 11).unwrap();
+        let bytes = header.to_bytes();
+        assert_eq!(bytes.len(), HEADER_LEN);
+        assert_eq!(
+            &bytes[..32],
+            &[
+                b'B', b'C', b'H', b'C', 1, 5, 0, 3, 0, 0, 0, 31, 0, 0, 0, 16, 0, 0, 0, 0x25, 0, 0,
+                0, 1, 0, 0, 0, 0, 0, 0, 0, 11
+            ][..]
+        );
+        assert_eq!(Header::from_bytes(&bytes), Ok(header));
+    }
+
+    #[test]
+    fn header_rejects_corruption_test() {
+        let bytes = Header::new(&get_code(), 11).unwrap().to_bytes();
+        for i in 0..HEADER_LEN {
+            let mut corrupted = bytes.clone();
+            corrupted[i] ^= 0x10;
+            assert!(Header::from_bytes(&corrupted).is_err(), "byte {}", i);
+        }
+        assert!(Header::from_bytes(&bytes[..20]).is_err());
+    }
+
+    #[test]
+    fn header_checks_k_test() {
+        let code = BchCode::new(31, 21, 3, &bitvec![1, 0, 0, 1, 0, 1]);
+        let bytes = Header::new(&code, 1).unwrap().to_bytes();
+        assert!(Header::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn header_checks_prime_poly_test() {
+        let bytes = Header::new(&get_code(), 11).unwrap().to_bytes();
+        let with_poly = |m: u8, poly: u32| {
+            let mut bytes = bytes.clone();
+            bytes[5] = m;
+            bytes[16..20].copy_from_slice(&poly.to_be_bytes());
+            let checksum = crc32(&bytes[..(HEADER_LEN - 4)]);
+            bytes[(HEADER_LEN - 4)..].copy_from_slice(&checksum.to_be_bytes());
+            bytes
+        };
+        assert!(Header::from_bytes(&with_poly(5, 0x25)).is_ok());
+        // x^5 + 1 is not irreducible
+        assert!(Header::from_bytes(&with_poly(5, 0x21)).is_err());
+        assert!(Header::from_bytes(&with_poly(31, 0x8000_0009)).is_err());
+    }
+
+    #[test]
+    fn huge_words_len_test() {
+        // a payload length claiming far more code words than follow
+        let mut header = Header::new(&get_code(), 1 << 40).unwrap().to_bytes();
+        header.extend(&[0u8; 16]);
+        let mut reader = ContainerReader::new(&header[..]).unwrap();
+        assert!(reader.read_words().is_err());
+    }
+
+    #[test]
+    fn overflowing_payload_len_test() {
+        let code = get_code();
+        assert!(Header::new(&code, u64::MAX).is_err());
+        assert!(Header::new(&code, u64::MAX / 8).is_err());
+
+        let mut bytes = Header::new(&code, 0).unwrap().to_bytes();
+        bytes[24..32].copy_from_slice(&u64::MAX.to_be_bytes());
+        let checksum = crc32(&bytes[..(HEADER_LEN - 4)]);
+        bytes[(HEADER_LEN - 4)..].copy_from_slice(&checksum.to_be_bytes());
+        assert!(Header::from_bytes(&bytes).is_err());
+        assert!(ContainerReader::new(&bytes[..]).is_err());
+    }
+
+    #[test]
+    fn write_words_test() {
+        let code = get_code();
+        let encoder = Encoder::from_code(&code);
+        let words: Vec<BitVec> = [[1u8, 2], [3, 4]]
+            .iter()
+            .map(|msg| encoder.encode(&framing::bytes_to_bits(msg)).unwrap())
+            .collect();
+        let mut writer = ContainerWriter::new(Vec::new(), &code).unwrap();
+        assert!(writer.write_words(5, &words).is_err());
+        writer.write_words(4, &words).unwrap();
+        assert_eq!(writer.into_inner(), write_container(&code, &[1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn non_primitive_code_rejected_test() {
+        let code = BchCode::new_non_primitive(21, 12, 2, &bitvec![1, 0, 0, 0, 0, 1, 1]);
+        assert!(ContainerWriter::new(Vec::new(), &code).is_err());
+    }
+
+    #[test]
+    fn write_read_test() {
+        let code = BchCode::new(25, 10, 3, &bitvec![1, 0, 0, 1, 0, 1]).with_first_root(0);
+        let code = code.with_derived_k();
+        let payload: Vec<u8> = (0..100).map(|i| (i * 7) as u8).collect();
+        let mut container = write_container(&code, &payload);
+        let blocks = framing::block_count(code.k, 100);
+        assert_eq!(container.len(), HEADER_LEN + (blocks * 25).div_ceil(8));
+
+        let header = Header::new(&code, 100).unwrap();
+        for (block, bit) in &[(0, 2), (3, 24), (blocks - 1, 0)] {
+            let offset = header.bit_offset(*block, *bit);
+            container[offset / 8] ^= 0x80 >> (offset % 8);
+        }
+        let mut reader = ContainerReader::new(&container[..]).unwrap();
+        assert_eq!(reader.header(), &header);
+        let decoder = BerlekampDecoder::from_code(&code);
+        let encoder = Encoder::from_code(&code);
+        let mut stats = DecodeStats::default();
+        let mut bits = BitVec::new();
+        for (block, word) in reader.read_words().unwrap().iter().enumerate() {
+            let (msg, block_stats) =
+                framing::decode_block(&decoder, &encoder, code.k, block, word).unwrap();
+            stats.add(&block_stats);
+            bits.extend(msg.iter());
+        }
+        assert_eq!(&framing::bits_to_bytes(&bits)[..100], &payload[..]);
+        assert_eq!(stats.corrected_bits, 3);
+    }
+
+    #[test]
+    fn truncated_words_test() {
+        let container = write_container(&get_code(), &[1, 2, 3, 4, 5]);
+        let mut reader = ContainerReader::new(&container[..container.len() - 1]).unwrap();
+        assert!(reader.read_words().is_err());
+        assert!(ContainerReader::new(&container[..10]).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use bitvec::BitVec;
+use bitvec::prelude::BitVec;
 
 pub trait Decoder {
     fn decode(&self, encoded: &BitVec) -> Result<(BitVec, BitVec), String>;
@@ -0,0 +1,169 @@
+use crate::bch_code::BchCode;
+use crate::common;
+use crate::galois_field::GaloisField;
+use bitvec::prelude::*;
+
+/// One primitive narrow-sense BCH code of length 2^m - 1, t being the
+/// largest designed error correcting capability of its generator.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodeDesign {
+    pub n: i32,
+    pub k: i32,
+    pub t: i32,
+    pub gen_poly: BitVec,
+}
+
+impl CodeDesign {
+    pub fn designed_distance(&self) -> i32 {
+        2 * self.t + 1
+    }
+
+    pub fn rate(&self) -> f64 {
+        self.k as f64 / self.n as f64
+    }
+
+    pub fn to_code(&self, prime_poly: &BitVec) -> BchCode {
+        BchCode::new(self.n, self.k, self.t, prime_poly)
+    }
+}
+
+/// All distinct primitive narrow-sense codes over the field of the
+/// primitive polynomial, in order of growing t. Taking the cyclotomic
+/// cosets in order of their smallest power, the generator of the first j
+/// cosets has consecutive roots up to the smallest power of coset j + 1.
+pub fn primitive_codes(prime_poly: &BitVec) -> Vec<CodeDesign> {
+    let field = GaloisField::new(prime_poly);
+    let n = field.order();
+    let layers = common::get_n_disjunctive_layers(n as u32 / 2, n as usize + 1);
+
+    let mut designs: Vec<CodeDesign> = Vec::new();
+    let mut gen_poly = bitvec![1];
+    for (i, layer) in layers.iter().enumerate() {
+        let min_pol = common::calculate_layer_min_pol(layer, &field);
+        gen_poly = common::finite_multiply_bitvecs_vec(&vec![gen_poly, min_pol]);
+
+        let next_root = layers.get(i + 1).map_or(n, |next| next[0] as i32);
+        let t = (next_root - 1) / 2;
+        if designs.last().map_or(true, |last| t > last.t) {
+            designs.push(CodeDesign {
+                n: n,
+                k: n - (gen_poly.len() as i32 - 1),
+                t: t,
+                gen_poly: gen_poly.clone(),
+            });
+        }
+    }
+    designs
+}
+
+/// The code of every t from 1 up to the largest achievable, the one of
+/// primitive_codes with the smallest t not below it. Codes of consecutive
+/// t share a generator when no coset adds roots between them.
+pub fn codes_for_every_t(prime_poly: &BitVec) -> Vec<CodeDesign> {
+    let designs = primitive_codes(prime_poly);
+    let max_t = designs.last().map_or(0, |design| design.t);
+    (1..=max_t)
+        .map(|t| {
+            let design = designs.iter().find(|design| design.t >= t).unwrap();
+            CodeDesign {
+                t: t,
+                ..design.clone()
+            }
+        })
+        .collect()
+}
+
+/// Polynomial in base 2^bits_per_digit, coefficients of the lowest degrees
+/// in the rightmost digit, as in the tables of Lin and Costello.
+pub fn poly_to_radix(poly: &BitVec, bits_per_digit: usize) -> String {
+    let digits: Vec<char> = poly
+        .iter()
+        .by_vals()
+        .rev()
+        .collect::<Vec<bool>>()
+        .chunks(bits_per_digit)
+        .map(|chunk| {
+            let digit = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |digit, (i, bit)| digit | (*bit as u32) << i);
+            std::char::from_digit(digit, 16).unwrap()
+        })
+        .collect();
+    digits.iter().rev().collect()
+}
+
+pub fn poly_to_octal(poly: &BitVec) -> String {
+    poly_to_radix(poly, 3)
+}
+
+pub fn poly_to_hex(poly: &BitVec) -> String {
+    poly_to_radix(poly, 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(designs: &[CodeDesign]) -> Vec<(i32, i32, String)> {
+        designs
+            .iter()
+            .map(|design| (design.k, design.t, poly_to_octal(&design.gen_poly)))
+            .collect()
+    }
+
+    #[test]
+    fn poly_to_radix_test() {
+        let poly = bitvec![1, 1, 1, 0, 1, 0, 0, 0, 1];
+        assert_eq!(poly_to_octal(&poly), "721");
+        assert_eq!(poly_to_hex(&poly), "1d1");
+        assert_eq!(poly_to_hex(&bitvec![1, 0, 0, 1, 1]), "13");
+    }
+
+    #[test]
+    fn primitive_codes_m4_test() {
+        let designs = primitive_codes(&bitvec![1, 0, 0, 1, 1]);
+        let expected = vec![
+            (11, 1, "23".to_owned()),
+            (7, 2, "721".to_owned()),
+            (5, 3, "2467".to_owned()),
+            (1, 7, "77777".to_owned()),
+        ];
+        assert_eq!(summary(&designs), expected);
+        assert_eq!(designs[1].designed_distance(), 5);
+    }
+
+    #[test]
+    fn primitive_codes_m5_test() {
+        // Lin and Costello, table of BCH codes of length 31
+        let designs = primitive_codes(&bitvec![1, 0, 0, 1, 0, 1]);
+        let expected = vec![
+            (26, 1, "45".to_owned()),
+            (21, 2, "3551".to_owned()),
+            (16, 3, "107657".to_owned()),
+            (11, 5, "5423325".to_owned()),
+            (6, 7, "313365047".to_owned()),
+            (1, 15, "17777777777".to_owned()),
+        ];
+        assert_eq!(summary(&designs), expected);
+    }
+
+    #[test]
+    fn codes_for_every_t_test() {
+        let designs = codes_for_every_t(&bitvec![1, 0, 0, 1, 1]);
+        let ts: Vec<(i32, i32)> = designs.iter().map(|design| (design.k, design.t)).collect();
+        assert_eq!(
+            ts,
+            vec![(11, 1), (7, 2), (5, 3), (1, 4), (1, 5), (1, 6), (1, 7)]
+        );
+        assert_eq!(designs[3].gen_poly, designs[6].gen_poly);
+    }
+
+    #[test]
+    fn primitive_codes_match_bch_code_test() {
+        let prime_poly = common::get_primitive_poly(7).unwrap();
+        for design in primitive_codes(&prime_poly) {
+            assert_eq!(design.to_code(&prime_poly).gen_poly(), design.gen_poly);
+        }
+    }
+}
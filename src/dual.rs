@@ -0,0 +1,138 @@
+use crate::bch_bitvec::*;
+use crate::bch_code::BchCode;
+use crate::encoder::Encoder;
+use crate::simple_decoder::SimpleDecoder;
+use bitvec::prelude::*;
+
+/// Dual of a cyclic code of length n with generator g(x). The check
+/// polynomial is h(x) = (x^n + 1) / g(x) and the dual is the cyclic code of
+/// dimension n - k generated by the reciprocal of h(x).
+#[derive(Clone, Debug, PartialEq)]
+pub struct DualCode {
+    pub n: i32,
+    pub k: i32,
+    pub check_poly: BitVec,
+    pub gen_poly: BitVec,
+}
+
+impl DualCode {
+    pub fn new(n: i32, gen_poly: &BitVec) -> DualCode {
+        let check_poly = get_check_poly(n, gen_poly);
+        let gen_poly: BitVec = check_poly.iter().rev().collect();
+        DualCode {
+            n: n,
+            k: n - check_poly.len() as i32 + 1,
+            check_poly: check_poly,
+            gen_poly: gen_poly,
+        }
+    }
+
+    /// Shortened codes are not cyclic, their dual is not built here.
+    pub fn from_code(code: &BchCode) -> DualCode {
+        if code.n != code.cycle_len {
+            panic!(
+                "Bad code parameters. Dual of a shortened code, n: {}, cycle length: {}",
+                code.n, code.cycle_len
+            );
+        }
+        DualCode::new(code.n, &code.gen_poly())
+    }
+
+    pub fn encoder(&self) -> Encoder {
+        Encoder::new_with_gen_poly(self.n, self.k, 0, self.gen_poly.clone())
+    }
+
+    /// The dual has no designed distance, t has to be given by the caller.
+    pub fn decoder(&self, t: i32) -> SimpleDecoder {
+        SimpleDecoder::new_with_gen_poly(self.n, self.k, t, self.gen_poly.clone())
+    }
+}
+
+/// Check polynomial h(x) = (x^n + 1) / g(x), g(x) has to divide x^n + 1.
+pub fn get_check_poly(n: i32, gen_poly: &BitVec) -> BitVec {
+    let mut x_n_plus_1 = bitvec![1];
+    x_n_plus_1.extend(bitvec![0; n as usize - 1]);
+    x_n_plus_1.push(true);
+
+    let remainder = x_n_plus_1.remainder_divide(gen_poly).unwrap();
+    if remainder.any() {
+        panic!(
+            "Bad coder parameters. gen: {:?} does not divide x^{} + 1",
+            gen_poly, n
+        );
+    }
+    x_n_plus_1.quotient_divide(gen_poly).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common;
+    use crate::decoder::Decoder;
+
+    fn get_simplex_code() -> DualCode {
+        DualCode::from_code(&BchCode::new(7, 4, 1, &bitvec![1, 0, 1, 1]))
+    }
+
+    #[test]
+    fn get_check_poly_test() {
+        let result = get_check_poly(7, &bitvec![1, 0, 1, 1]);
+        let expected = bitvec![1, 0, 1, 1, 1];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn check_poly_times_gen_poly_test() {
+        let gen_poly = common::get_gen_poly(2, 1, &bitvec![1, 0, 0, 1, 1]);
+        let check_poly = get_check_poly(15, &gen_poly);
+        let product = common::finite_multiply_bitvecs_vec(&vec![gen_poly, check_poly]);
+        let mut expected = bitvec![1];
+        expected.extend(bitvec![0; 14]);
+        expected.push(true);
+        assert_eq!(product, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn dual_of_shortened_code_fails() {
+        DualCode::from_code(&BchCode::new(25, 10, 3, &bitvec![1, 0, 0, 1, 0, 1]));
+    }
+
+    #[test]
+    fn dual_of_dual_test() {
+        let code = BchCode::new(15, 7, 2, &bitvec![1, 0, 0, 1, 1]);
+        let dual = DualCode::from_code(&code);
+        assert_eq!(dual.k, 8);
+        let dual_of_dual = DualCode::new(dual.n, &dual.gen_poly);
+        assert_eq!(dual_of_dual.k, 7);
+        assert_eq!(dual_of_dual.gen_poly, code.gen_poly());
+    }
+
+    #[test]
+    fn simplex_code_test() {
+        let dual = get_simplex_code();
+        assert_eq!(dual.k, 3);
+        assert_eq!(dual.gen_poly, bitvec![1, 1, 1, 0, 1]);
+
+        let encoder = dual.encoder();
+        for msg in 1..8u8 {
+            let msg: BitVec = (0..3).map(|i| msg & (4 >> i) != 0).collect();
+            assert_eq!(encoder.encode(&msg).unwrap().count_ones(), 4);
+        }
+    }
+
+    #[test]
+    fn simplex_code_decode_single_errors_test() {
+        let dual = get_simplex_code();
+        let encoder = dual.encoder();
+        let decoder = dual.decoder(1);
+        let msg = bitvec![1, 0, 1];
+        let encoded = encoder.encode(&msg).unwrap();
+        for i in 0..7 {
+            let mut received = encoded.clone();
+            received.inverse_nth(i);
+            let (decoded, _) = decoder.decode(&received).unwrap();
+            assert_eq!(decoded, msg);
+        }
+    }
+}
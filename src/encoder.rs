@@ -1,7 +1,9 @@
 use crate::bch_bitvec::*;
+use crate::bch_code::BchCode;
 use crate::common;
-use bitvec::*;
+use bitvec::prelude::*;
 
+#[derive(Clone)]
 pub struct Encoder {
     n: i32,
     k: i32,
@@ -10,12 +12,15 @@ pub struct Encoder {
 
 impl Encoder {
     pub fn new(n: i32, k: i32, t: i32, prime_poly: &BitVec) -> Encoder {
-        let prime_poly_degree = prime_poly.len() as i32 - 1;
-        let gen_poly = common::get_gen_poly(prime_poly_degree, t, prime_poly);
-        common::validate_params(n, k, &gen_poly, prime_poly);
+        Encoder::from_code(&BchCode::new(n, k, t, prime_poly))
+    }
+
+    pub fn from_code(code: &BchCode) -> Encoder {
+        let gen_poly = code.gen_poly();
+        common::validate_params(code.n, code.k, &gen_poly, &code.prime_poly);
         Encoder {
-            n: n,
-            k: k,
+            n: code.n,
+            k: code.k,
             gen_poly: gen_poly,
         }
     }
@@ -28,7 +33,7 @@ impl Encoder {
         }
     }
 
-    pub fn encode(self, data: &BitVec) -> Result<BitVec, String> {
+    pub fn encode(&self, data: &BitVec) -> Result<BitVec, String> {
         if data.len() as i32 > self.k {
             return Err("Encode: to long data!".to_owned());
         }
@@ -38,7 +43,7 @@ impl Encoder {
         data_clone.extend(bitvec![0; control_len as usize]);
 
         let division_remainder = data_clone.remainder_divide(&self.gen_poly)?;
-        data_clone += division_remainder;
+        data_clone = data_clone.finite_add(&division_remainder);
 
         Ok(data_clone)
     }
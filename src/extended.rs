@@ -0,0 +1,180 @@
+use crate::decoder::*;
+use crate::encoder::Encoder;
+use bitvec::prelude::*;
+
+/// Extended BCH code: an overall parity bit is appended to every codeword,
+/// which raises the minimum distance from 2t + 1 to 2t + 2.
+#[derive(Clone)]
+pub struct ExtendedEncoder {
+    n: i32,
+    t: i32,
+    encoder: Encoder,
+}
+
+impl ExtendedEncoder {
+    pub fn new(n: i32, k: i32, t: i32, prime_poly: &BitVec) -> ExtendedEncoder {
+        ExtendedEncoder {
+            n: n,
+            t: t,
+            encoder: Encoder::new(n, k, t, prime_poly),
+        }
+    }
+
+    /// Length of the extended codeword.
+    pub fn n(&self) -> i32 {
+        self.n + 1
+    }
+
+    /// Designed distance of the extended code.
+    pub fn d(&self) -> i32 {
+        2 * self.t + 2
+    }
+
+    pub fn encode(&self, data: &BitVec) -> Result<BitVec, String> {
+        let mut encoded = self.encoder.encode(data)?;
+        let parity = encoded.count_ones() % 2 == 1;
+        encoded.push(parity);
+        Ok(encoded)
+    }
+}
+
+/// Decodes extended codewords with any of the BCH decoders. The inner decoder
+/// corrects up to t errors, the overall parity bit is then used to detect t + 1.
+#[derive(Clone)]
+pub struct ExtendedDecoder<D: Decoder> {
+    n: i32,
+    k: i32,
+    t: i32,
+    decoder: D,
+    encoder: Encoder,
+}
+
+impl<D: Decoder> ExtendedDecoder<D> {
+    pub fn new(n: i32, k: i32, t: i32, prime_poly: &BitVec, decoder: D) -> ExtendedDecoder<D> {
+        ExtendedDecoder {
+            n: n,
+            k: k,
+            t: t,
+            decoder: decoder,
+            encoder: Encoder::new(n, k, t, prime_poly),
+        }
+    }
+
+    /// Length of the extended codeword.
+    pub fn n(&self) -> i32 {
+        self.n + 1
+    }
+
+    /// Designed distance of the extended code.
+    pub fn d(&self) -> i32 {
+        2 * self.t + 2
+    }
+}
+
+impl<D: Decoder> Decoder for ExtendedDecoder<D> {
+    fn decode(&self, encoded: &BitVec) -> Result<(BitVec, BitVec), String> {
+        if encoded.is_empty() || encoded.len() > self.n() as usize {
+            return Err("Encoded data has wrong length!".to_owned());
+        }
+
+        let inner: BitVec = encoded.iter().take(encoded.len() - 1).collect();
+        let (decoded, _) = self.decoder.decode(&inner)?;
+
+        // every extended codeword has even weight, so the parity of the received
+        // word is the parity of the number of errors
+        let corrected = self.encoder.encode(&decoded)?;
+        let corrected_count = (corrected.clone() ^ inner).count_ones() as i32;
+        let errors_odd = encoded.count_ones() % 2 == 1;
+        if (corrected_count % 2 == 1) != errors_odd && corrected_count >= self.t {
+            return Err("Detected t + 1 errors. Could not decode".to_owned());
+        }
+
+        let mut control: BitVec = corrected.iter().skip(self.k as usize).collect();
+        control.push(corrected.count_ones() % 2 == 1);
+        Ok((decoded, control))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bch_bitvec::*;
+    use crate::berlekamp_decoder::BerlekampDecoder;
+    use crate::simple_decoder::SimpleDecoder;
+    use itertools::Itertools;
+
+    fn get_berlekamp_decoder() -> ExtendedDecoder<BerlekampDecoder> {
+        let prime_poly = bitvec![1, 0, 0, 1, 1];
+        let decoder = BerlekampDecoder::new(15, 7, 2, &prime_poly);
+        ExtendedDecoder::new(15, 7, 2, &prime_poly, decoder)
+    }
+
+    #[test]
+    fn extended_params_test() {
+        let encoder = ExtendedEncoder::new(15, 7, 2, &bitvec![1, 0, 0, 1, 1]);
+        assert_eq!(encoder.n(), 16);
+        assert_eq!(encoder.d(), 6);
+
+        let decoder = get_berlekamp_decoder();
+        assert_eq!(decoder.n(), 16);
+        assert_eq!(decoder.d(), 6);
+    }
+
+    #[test]
+    fn encode_appends_parity_test() {
+        let encoder = ExtendedEncoder::new(7, 4, 1, &bitvec![1, 0, 1, 1]);
+        let result = encoder.encode(&bitvec![1, 0, 0, 1]).unwrap();
+        assert_eq!(result, bitvec![1, 0, 0, 1, 1, 1, 0, 0]);
+
+        let result = encoder.encode(&bitvec![1, 0, 0, 0]).unwrap();
+        assert_eq!(result, bitvec![1, 0, 0, 0, 1, 0, 1, 1]);
+    }
+
+    #[test]
+    fn decode_corrects_error_in_parity_bit_test() {
+        let prime_poly = bitvec![1, 0, 1, 1];
+        let encoder = ExtendedEncoder::new(7, 4, 1, &prime_poly);
+        let decoder = ExtendedDecoder::new(
+            7,
+            4,
+            1,
+            &prime_poly,
+            SimpleDecoder::new(7, 4, 1, &prime_poly),
+        );
+
+        let mut encoded = encoder.encode(&bitvec![1, 0, 0, 1]).unwrap();
+        encoded.inverse_nth(7);
+        let (decoded, control) = decoder.decode(&encoded).unwrap();
+        assert_eq!(decoded, bitvec![1, 0, 0, 1]);
+        assert_eq!(control, bitvec![1, 1, 0, 0]);
+    }
+
+    #[test]
+    fn decode_all_t_errors_test() {
+        let msg = bitvec![1, 0, 1, 1, 0, 0, 1];
+        let encoder = ExtendedEncoder::new(15, 7, 2, &bitvec![1, 0, 0, 1, 1]);
+        let decoder = get_berlekamp_decoder();
+        let encoded = encoder.encode(&msg).unwrap();
+
+        for places in (0..16).combinations(2) {
+            let mut received = encoded.clone();
+            places.iter().for_each(|place| received.inverse_nth(*place));
+            let (decoded, _) = decoder.decode(&received).unwrap();
+            assert_eq!(decoded, msg);
+        }
+    }
+
+    #[test]
+    fn decode_detects_all_t_plus_1_errors_test() {
+        let msg = bitvec![1, 0, 1, 1, 0, 0, 1];
+        let encoder = ExtendedEncoder::new(15, 7, 2, &bitvec![1, 0, 0, 1, 1]);
+        let decoder = get_berlekamp_decoder();
+        let encoded = encoder.encode(&msg).unwrap();
+
+        for places in (0..16).combinations(3) {
+            let mut received = encoded.clone();
+            places.iter().for_each(|place| received.inverse_nth(*place));
+            assert!(decoder.decode(&received).is_err());
+        }
+    }
+}
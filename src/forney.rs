@@ -0,0 +1,135 @@
+use crate::galois_field::GaloisField;
+
+/// Error evaluator omega(x) = S(x) * sigma(x) mod x^(2t), where
+/// S(x) = S1 + S2 x + ... is built from the syndromes as alpha powers.
+pub fn error_evaluator(
+    field: &GaloisField,
+    syndroms_alphas: &Vec<i32>,
+    sigma: &Vec<i32>,
+) -> Vec<i32> {
+    let syndrom_poly: Vec<i32> = syndroms_alphas.iter().rev().cloned().collect();
+    let product = field.multiply_polys(&syndrom_poly, sigma);
+    let low_terms = product.len().saturating_sub(syndroms_alphas.len());
+    field.add_polys(product[low_terms..].to_vec(), Vec::new())
+}
+
+/// In characteristic 2 only odd powers of x survive the derivative.
+pub fn formal_derivative(field: &GaloisField, poly: &Vec<i32>) -> Vec<i32> {
+    if poly.is_empty() {
+        return Vec::new();
+    }
+    let degree = poly.len() - 1;
+    let derivative = poly
+        .iter()
+        .take(degree)
+        .enumerate()
+        .map(|(i, alpha)| if (degree - i) % 2 == 1 { *alpha } else { -1 })
+        .collect();
+    field.add_polys(derivative, Vec::new())
+}
+
+/// Forney's formula for the error at locator X = alpha^locator, root being
+/// the power of X^-1 found in sigma. For syndromes starting at alpha^first_root
+/// e = X^(1 - first_root) * omega(X^-1) / sigma'(X^-1).
+pub fn error_value(
+    field: &GaloisField,
+    evaluator: &Vec<i32>,
+    sigma_derivative: &Vec<i32>,
+    root: i32,
+    first_root: i32,
+) -> Result<i32, String> {
+    let denominator = field.evaluate_poly(sigma_derivative, root);
+    if denominator == -1 {
+        return Err("Could not compute error value. Could not decode".to_owned());
+    }
+    let locator = field.alpha_mod_order(-root);
+    let numerator = field.multiply_alphas(
+        field.evaluate_poly(evaluator, root),
+        field.alpha_mod_order(locator * (1 - first_root)),
+    );
+    Ok(field.divide_alphas(numerator, denominator))
+}
+
+/// Error values (as alpha powers) for every root of the error locator
+/// returned by `berlekamp_decoder::find_error_locator`.
+pub fn error_values(
+    field: &GaloisField,
+    syndroms_alphas: &Vec<i32>,
+    sigma: &Vec<i32>,
+    roots: &Vec<i32>,
+    first_root: i32,
+) -> Result<Vec<i32>, String> {
+    let evaluator = error_evaluator(field, syndroms_alphas, sigma);
+    let sigma_derivative = formal_derivative(field, sigma);
+    roots
+        .iter()
+        .map(|root| error_value(field, &evaluator, &sigma_derivative, *root, first_root))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::berlekamp_decoder::find_error_locator;
+    use bitvec::prelude::*;
+
+    fn get_field() -> GaloisField {
+        GaloisField::new(&bitvec![1, 0, 0, 1, 0, 1])
+    }
+
+    fn get_syndroms(field: &GaloisField, errors: &Vec<(i32, i32)>, first_root: i32) -> Vec<i32> {
+        (first_root..(first_root + 6))
+            .map(|j| {
+                errors.iter().fold(-1, |sum, (locator, value)| {
+                    field.add_alphas(sum, field.multiply_alphas(*value, locator * j))
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn formal_derivative_test() {
+        let field = get_field();
+        // x^3 + a^11 x^2 + a^2 x + 1 -> x^2 + a^2
+        let result = formal_derivative(&field, &vec![0, 11, 2, 0]);
+        assert_eq!(result, vec![0, -1, 2]);
+        assert_eq!(formal_derivative(&field, &vec![5]), vec![]);
+        assert_eq!(formal_derivative(&field, &vec![]), vec![]);
+    }
+
+    #[test]
+    fn error_evaluator_test() {
+        let field = get_field();
+        // single error of value a^3 at a^7: sigma = a^7 x + 1, omega = S1
+        let syndroms = get_syndroms(&field, &vec![(7, 3)], 1);
+        let result = error_evaluator(&field, &syndroms, &vec![7, 0]);
+        assert_eq!(result, vec![10]);
+    }
+
+    #[test]
+    fn error_values_test() {
+        let field = get_field();
+        for first_root in 0..3 {
+            let errors = vec![(27, 5), (22, 0), (9, 30)];
+            let syndroms = get_syndroms(&field, &errors, first_root);
+            let (sigma, _) = find_error_locator(&field, &syndroms, 1);
+            let roots = field.find_roots(&sigma);
+            assert_eq!(roots, vec![4, 9, 22]);
+
+            let values = error_values(&field, &syndroms, &sigma, &roots, first_root).unwrap();
+            assert_eq!(values, vec![5, 0, 30]);
+        }
+    }
+
+    #[test]
+    fn binary_error_values_are_one_test() {
+        let field = get_field();
+        let errors = vec![(3, 0), (17, 0)];
+        let syndroms = get_syndroms(&field, &errors, 1);
+        let (sigma, _) = find_error_locator(&field, &syndroms, 2);
+        let roots = field.find_roots(&sigma);
+
+        let values = error_values(&field, &syndroms, &sigma, &roots, 1).unwrap();
+        assert_eq!(values, vec![0, 0]);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::channel::Channel;
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use bitvec::prelude::*;
+use rand::RngCore;
+
+/// Counts of a decoded payload. Uncorrectable blocks are kept as received.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DecodeStats {
+    pub blocks: usize,
+    pub corrected_bits: usize,
+    pub uncorrectable_blocks: Vec<usize>,
+}
+
+/// Bits of the bytes, most significant bit first.
+pub fn bytes_to_bits(bytes: &[u8]) -> BitVec {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| byte & (1 << i) != 0))
+        .collect()
+}
+
+/// Bytes of the bits, most significant bit first, the last one zero padded.
+pub fn bits_to_bytes(bits: &BitVec) -> Vec<u8> {
+    let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+    for (i, bit) in bits.iter().by_vals().enumerate() {
+        if bit {
+            bytes[i / 8] |= 0x80 >> (i % 8);
+        }
+    }
+    bytes
+}
+
+/// Number of k-bit blocks holding a payload of the given byte count.
+pub fn block_count(k: i32, payload_len: u64) -> usize {
+    (payload_len * 8).div_ceil(k as u64) as usize
+}
+
+/// Code words packed back to back, the last byte zero padded.
+pub fn pack_words(words: &[BitVec]) -> Vec<u8> {
+    let mut bits = BitVec::new();
+    for word in words {
+        bits.extend(word.iter());
+    }
+    bits_to_bytes(&bits)
+}
+
+/// The first `count` n-bit code words of packed data.
+pub fn unpack_words(n: i32, count: usize, data: &[u8]) -> Result<Vec<BitVec>, String> {
+    let bits = bytes_to_bits(data);
+    if bits.len() < count * n as usize {
+        return Err(format!(
+            "Packed data is truncated. Expected {} code words of {} bits",
+            count, n
+        ));
+    }
+    Ok((0..count)
+        .map(|block| {
+            bits.iter()
+                .skip(block * n as usize)
+                .take(n as usize)
+                .collect()
+        })
+        .collect())
+}
+
+/// Outcome of decoding one block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockStats {
+    pub block: usize,
+    pub corrected_bits: usize,
+    pub uncorrectable: bool,
+}
+
+impl DecodeStats {
+    pub fn add(&mut self, block: &BlockStats) {
+        self.blocks += 1;
+        self.corrected_bits += block.corrected_bits;
+        if block.uncorrectable {
+            self.uncorrectable_blocks.push(block.block);
+        }
+    }
+}
+
+/// Message of one received word, taken as received when the decoder fails.
+/// Corrected bits are counted against the re-encoded message.
+pub fn decode_block(
+    decoder: &dyn Decoder,
+    encoder: &Encoder,
+    k: i32,
+    block: usize,
+    word: &BitVec,
+) -> Result<(BitVec, BlockStats), String> {
+    match decoder.decode(word) {
+        Ok((decoded, _)) => {
+            let corrected = encoder.encode(&decoded)?;
+            let corrected_bits = word
+                .iter()
+                .zip(corrected.iter())
+                .filter(|(received, sent)| received != sent)
+                .count();
+            Ok((
+                decoded,
+                BlockStats {
+                    block: block,
+                    corrected_bits: corrected_bits,
+                    uncorrectable: false,
+                },
+            ))
+        }
+        Err(_) => Ok((
+            word.iter().take(k as usize).collect(),
+            BlockStats {
+                block: block,
+                corrected_bits: 0,
+                uncorrectable: true,
+            },
+        )),
+    }
+}
+
+/// Sends every code word through the channel. Returns the received words
+/// and the flipped (block, bit) positions, bit 0 being the first bit of the
+/// block.
+pub fn transmit_words(
+    words: &[BitVec],
+    channel: &mut dyn Channel,
+    rng: &mut dyn RngCore,
+) -> (Vec<BitVec>, Vec<(usize, usize)>) {
+    let mut flips = Vec::new();
+    let received = words
+        .iter()
+        .enumerate()
+        .map(|(block, word)| {
+            let received = channel.transmit(word, rng);
+            for (bit, (sent, got)) in word.iter().zip(received.iter()).enumerate() {
+                if sent != got {
+                    flips.push((block, bit));
+                }
+            }
+            received
+        })
+        .collect();
+    (received, flips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bch_code::BchCode;
+    use crate::berlekamp_decoder::BerlekampDecoder;
+    use crate::channel::FixedErrorsChannel;
+    use crate::common;
+    use crate::rng::test_rng;
+
+    fn get_code() -> BchCode {
+        BchCode::new(31, 16, 3, &bitvec![1, 0, 0, 1, 0, 1])
+    }
+
+    fn random_words(code: &BchCode, encoder: &Encoder, count: usize) -> Vec<BitVec> {
+        let mut rng = test_rng();
+        (0..count)
+            .map(|_| {
+                let msg = common::get_random_msg(code.k as usize, &mut rng);
+                encoder.encode(&msg).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn bytes_to_bits_test() {
+        let bits = bytes_to_bits(&[0b1010_0000, 0x01]);
+        assert_eq!(bits.len(), 16);
+        assert_eq!(
+            bits,
+            bitvec![1, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]
+        );
+        assert_eq!(bits_to_bytes(&bits), vec![0b1010_0000, 0x01]);
+        assert_eq!(bits_to_bytes(&bitvec![1, 1, 1]), vec![0b1110_0000]);
+    }
+
+    #[test]
+    fn block_count_test() {
+        assert_eq!(block_count(16, 3), 2);
+        assert_eq!(block_count(16, 4), 2);
+        assert_eq!(block_count(16, 0), 0);
+    }
+
+    #[test]
+    fn pack_words_test() {
+        let words = vec![bitvec![1, 0, 1], bitvec![1, 1, 1], bitvec![0, 0, 1]];
+        let packed = pack_words(&words);
+        assert_eq!(packed, vec![0b1011_1100, 0b1000_0000]);
+        assert_eq!(unpack_words(3, 3, &packed), Ok(words));
+        assert!(unpack_words(3, 6, &packed).is_err());
+    }
+
+    #[test]
+    fn decode_block_test() {
+        let code = get_code();
+        let encoder = Encoder::from_code(&code);
+        let decoder = BerlekampDecoder::from_code(&code);
+        let msg = bytes_to_bits(b"BC");
+        let word = encoder.encode(&msg).unwrap();
+
+        let mut received = word.clone();
+        for bit in &[0, 3, 30] {
+            let flipped = !received[*bit];
+            received.set(*bit, flipped);
+        }
+        let (decoded, stats) = decode_block(&decoder, &encoder, code.k, 4, &received).unwrap();
+        assert_eq!(decoded, msg);
+        assert_eq!((stats.block, stats.corrected_bits), (4, 3));
+        assert!(!stats.uncorrectable);
+
+        // seven errors are beyond t, the message is taken as received
+        let mut received = word;
+        for bit in 0..7 {
+            let flipped = !received[bit];
+            received.set(bit, flipped);
+        }
+        let (decoded, stats) = decode_block(&decoder, &encoder, code.k, 0, &received).unwrap();
+        assert_eq!(decoded, received.iter().take(16).collect::<BitVec>());
+        assert!(stats.uncorrectable);
+    }
+
+    #[test]
+    fn transmit_words_test() {
+        let code = get_code();
+        let encoder = Encoder::from_code(&code);
+        let decoder = BerlekampDecoder::from_code(&code);
+        let words = random_words(&code, &encoder, 25);
+
+        let mut channel = FixedErrorsChannel::new(3);
+        let (received, flips) = transmit_words(&words, &mut channel, &mut test_rng());
+        assert_eq!(received.len(), 25);
+        assert_eq!(flips.len(), 3 * 25);
+        for (block, bit) in &flips {
+            assert_ne!(words[*block][*bit], received[*block][*bit]);
+        }
+
+        let mut stats = DecodeStats::default();
+        for (block, word) in received.iter().enumerate() {
+            let (decoded, block_stats) =
+                decode_block(&decoder, &encoder, code.k, block, word).unwrap();
+            assert_eq!(encoder.encode(&decoded).unwrap(), words[block]);
+            stats.add(&block_stats);
+        }
+        assert_eq!(stats.corrected_bits, flips.len());
+    }
+}
@@ -0,0 +1,263 @@
+use bitvec::prelude::*;
+
+/// GF(2^m) built from a primitive polynomial of degree m.
+///
+/// Elements are handled as powers of alpha, -1 standing for zero. Polynomials
+/// over the field are vectors of such powers, highest degree first.
+#[derive(Clone, Debug)]
+pub struct GaloisField {
+    m: u32,
+    alpha_values: Vec<u32>,
+    alpha_powers: Vec<i32>,
+}
+
+impl GaloisField {
+    pub fn new(prime_poly: &BitVec) -> GaloisField {
+        let m = prime_poly.len() as u32 - 1;
+        let poly = prime_poly
+            .iter()
+            .by_vals()
+            .fold(0u32, |value, bit| (value << 1) | bit as u32);
+        let size = 1usize << m;
+
+        let mut alpha_values = Vec::with_capacity(size - 1);
+        let mut alpha_powers = vec![-1; size];
+        let mut value = 1u32;
+        for i in 0..(size - 1) {
+            alpha_values.push(value);
+            if alpha_powers[value as usize] == -1 {
+                alpha_powers[value as usize] = i as i32;
+            }
+            value <<= 1;
+            if value & (1 << m) != 0 {
+                value ^= poly;
+            }
+        }
+
+        GaloisField {
+            m: m,
+            alpha_values: alpha_values,
+            alpha_powers: alpha_powers,
+        }
+    }
+
+    pub fn m(&self) -> u32 {
+        self.m
+    }
+
+    /// Number of non zero elements.
+    pub fn order(&self) -> i32 {
+        self.alpha_values.len() as i32
+    }
+
+    pub fn alpha_mod_order(&self, alpha: i32) -> i32 {
+        let mut res = alpha % self.order();
+        if res < 0 {
+            res += self.order();
+        }
+        res
+    }
+
+    /// Binary representation of alpha^alpha, 0 for -1.
+    pub fn alpha_to_value(&self, alpha: i32) -> u32 {
+        if alpha == -1 {
+            return 0;
+        }
+        self.alpha_values[self.alpha_mod_order(alpha) as usize]
+    }
+
+    pub fn value_to_alpha(&self, value: u32) -> i32 {
+        self.alpha_powers[value as usize]
+    }
+
+    pub fn add_alphas(&self, a1: i32, a2: i32) -> i32 {
+        if a1 == -1 {
+            return a2;
+        } else if a2 == -1 {
+            return a1;
+        }
+        self.value_to_alpha(self.alpha_to_value(a1) ^ self.alpha_to_value(a2))
+    }
+
+    pub fn multiply_alphas(&self, a1: i32, a2: i32) -> i32 {
+        if a1 == -1 || a2 == -1 {
+            return -1;
+        }
+        self.alpha_mod_order(a1 + a2)
+    }
+
+    /// Divides by a non zero element.
+    pub fn divide_alphas(&self, a1: i32, a2: i32) -> i32 {
+        if a1 == -1 {
+            return -1;
+        }
+        self.alpha_mod_order(a1 - a2)
+    }
+
+    pub fn evaluate_poly(&self, poly: &Vec<i32>, x_alpha: i32) -> i32 {
+        poly.iter().fold(-1, |sum, alpha| {
+            self.add_alphas(self.multiply_alphas(sum, x_alpha), *alpha)
+        })
+    }
+
+    //TODO to doc: alphas in poly cant be negative
+    pub fn multiply_poly_by_alpha_with_x(
+        &self,
+        poly: &Vec<i32>,
+        x_power: i32,
+        x_alpha: i32,
+    ) -> Vec<i32> {
+        let mut result = vec![-1; poly.len() + x_power as usize];
+        for (i, alpha) in poly.iter().rev().enumerate() {
+            *result.iter_mut().rev().nth(x_power as usize + i).unwrap() =
+                self.multiply_alphas(x_alpha, *alpha);
+        }
+        result
+    }
+
+    pub fn add_polys(&self, poly: Vec<i32>, to_add: Vec<i32>) -> Vec<i32> {
+        let (mut longer, shorter) = if poly.len() >= to_add.len() {
+            (poly, to_add)
+        } else {
+            (to_add, poly)
+        };
+        for (longer_el, shorter_el) in longer.iter_mut().rev().zip(shorter.iter().rev()) {
+            *longer_el = self.add_alphas(*longer_el, *shorter_el);
+        }
+        // coefficients may cancel out, keep the highest one non zero
+        let leading_zeros = longer.iter().take_while(|alpha| **alpha == -1).count();
+        longer.split_off(leading_zeros)
+    }
+
+    pub fn multiply_polys(&self, p1: &Vec<i32>, p2: &Vec<i32>) -> Vec<i32> {
+        p2.iter()
+            .rev()
+            .enumerate()
+            .fold(Vec::new(), |sum, (x_power, alpha)| {
+                let to_add = self.multiply_poly_by_alpha_with_x(p1, x_power as i32, *alpha);
+                self.add_polys(sum, to_add)
+            })
+    }
+
+    /// Remainder of division by a polynomial with non zero leading coefficient.
+    pub fn remainder_divide_polys(&self, dividend: &Vec<i32>, divisor: &Vec<i32>) -> Vec<i32> {
+        let mut remainder = dividend.clone();
+        while remainder.len() >= divisor.len() {
+            let x_power = (remainder.len() - divisor.len()) as i32;
+            let x_alpha = self.divide_alphas(remainder[0], divisor[0]);
+            let to_add = self.multiply_poly_by_alpha_with_x(divisor, x_power, x_alpha);
+            remainder = self.add_polys(remainder, to_add);
+        }
+        remainder
+    }
+
+    /// Powers i in 1..=order such that alpha^i is a root of the polynomial.
+    pub fn find_roots(&self, poly: &Vec<i32>) -> Vec<i32> {
+        let mut roots = Vec::new();
+        for i in 1..(self.order() + 1) {
+            if self.evaluate_poly(poly, i) == -1 {
+                roots.push(i);
+            }
+        }
+        roots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_field() -> GaloisField {
+        GaloisField::new(&bitvec![1, 0, 0, 1, 0, 1])
+    }
+
+    #[test]
+    fn alpha_values_test() {
+        let field = GaloisField::new(&bitvec![1, 0, 1, 1]);
+        let values: Vec<u32> = (0..8).map(|i| field.alpha_to_value(i)).collect();
+        assert_eq!(values, vec![1, 2, 4, 3, 6, 7, 5, 1]);
+        assert_eq!(field.alpha_to_value(-1), 0);
+        assert_eq!(field.value_to_alpha(0), -1);
+        assert_eq!(field.value_to_alpha(5), 6);
+    }
+
+    #[test]
+    fn add_alphas_test() {
+        let field = GaloisField::new(&bitvec![1, 0, 1, 1]);
+        assert_eq!(field.add_alphas(0, 1), 3);
+        assert_eq!(field.add_alphas(4, 4), -1);
+        assert_eq!(field.add_alphas(-1, 4), 4);
+        assert_eq!(field.add_alphas(7, 1), 3);
+    }
+
+    #[test]
+    fn multiply_and_divide_alphas_test() {
+        let field = get_field();
+        assert_eq!(field.multiply_alphas(20, 20), 9);
+        assert_eq!(field.multiply_alphas(-1, 20), -1);
+        assert_eq!(field.divide_alphas(3, 20), 14);
+        assert_eq!(field.divide_alphas(-1, 20), -1);
+    }
+
+    #[test]
+    fn evaluate_poly_test() {
+        let field = get_field();
+        let poly = vec![27, 11, 2, 0];
+        assert_eq!(field.evaluate_poly(&poly, 4), -1);
+        assert_eq!(field.evaluate_poly(&poly, -1), 0);
+        assert_ne!(field.evaluate_poly(&poly, 5), -1);
+    }
+
+    #[test]
+    fn multiply_polys_test() {
+        let field = get_field();
+        // (x + alpha^4)(x + alpha^9)
+        let result = field.multiply_polys(&vec![0, 4], &vec![0, 9]);
+        assert_eq!(result, vec![0, field.add_alphas(4, 9), 13]);
+    }
+
+    #[test]
+    fn remainder_divide_polys_test() {
+        let field = get_field();
+        let divisor = vec![0, 4];
+        let dividend = field.multiply_polys(&divisor, &vec![3, -1, 7]);
+        assert_eq!(field.remainder_divide_polys(&dividend, &divisor), vec![]);
+
+        let dividend = field.add_polys(dividend, vec![12]);
+        assert_eq!(field.remainder_divide_polys(&dividend, &divisor), vec![12]);
+    }
+
+    #[test]
+    fn multiply_poly_by_alpha_with_x_test() {
+        let field = get_field();
+
+        let sigma = vec![0];
+        let result = field.multiply_poly_by_alpha_with_x(&sigma, 1, 2);
+        assert_eq!(result, vec![2, -1]);
+
+        let sigma = vec![2, 0];
+        let result = field.multiply_poly_by_alpha_with_x(&sigma, 2, 25);
+        assert_eq!(result, vec![27, 25, -1, -1]);
+
+        let sigma = vec![25, 26, -1, 3];
+        let result = field.multiply_poly_by_alpha_with_x(&sigma, 1, 25);
+        assert_eq!(result, vec![19, 20, -1, 28, -1]);
+    }
+
+    #[test]
+    fn add_polys_test() {
+        let field = get_field();
+        let sigma = vec![24, 2, 0];
+        let to_add = vec![27, 25, -1, -1];
+        let result = field.add_polys(sigma, to_add);
+        assert_eq!(result, vec![27, 11, 2, 0]);
+    }
+
+    #[test]
+    fn find_roots_test() {
+        let field = get_field();
+        let poly = vec![27, 11, 2, 0];
+        let result = field.find_roots(&poly);
+        assert_eq!(result, vec![4, 9, 22]);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::byte_codec::{BitOrder, ByteCodec};
+
+/// Default primitive polynomials of lib/bch, m = 5..15.
+const PRIM_POLY_TAB: [u32; 11] = [
+    0x25, 0x43, 0x83, 0x11d, 0x211, 0x409, 0x805, 0x1053, 0x201b, 0x402b, 0x8003,
+];
+
+/// Codes with the conventions of the Linux kernel lib/bch, as read from its
+/// sources. The test vectors come from tools/kernel_bch/reference.py and
+/// have not been checked against the kernel: until `make check` of
+/// tools/kernel_bch passes on a kernel tree the output is not known to
+/// match lib/bch. The ECC of a data buffer is the
+/// remainder of data(x) x^ecc_bits modulo the generator, data bits taken
+/// most significant first and the ECC bits left aligned in ecc_bytes()
+/// bytes, which are sized for m * t bits as in bch_init() even when the
+/// generator has a lower degree. The padding bits are zero and ignored on
+/// decoding. With swap_bits the bits of every data and ECC byte are
+/// reversed.
+/// Error locations are bit numbers as given by decode_bch(): location l is
+/// bit l % 8 (counted from the least significant one) of byte l / 8 of the
+/// data followed by the ECC.
+pub struct KernelBch {
+    m: u32,
+    t: i32,
+    prim_poly: u32,
+    codec: ByteCodec,
+}
+
+impl KernelBch {
+    /// As bch_init(), prim_poly 0 takes the kernel default of m.
+    pub fn new(m: u32, t: i32, prim_poly: u32, swap_bits: bool) -> Result<KernelBch, String> {
+        if m < 5 || m > 15 || t < 1 || m as i64 * t as i64 >= (1i64 << m) - 1 {
+            return Err(format!(
+                "Unsupported kernel BCH parameters. m: {}, t: {}",
+                m, t
+            ));
+        }
+        let prim_poly = match prim_poly {
+            0 => PRIM_POLY_TAB[m as usize - 5],
+            poly if poly >> m == 1 => poly,
+            poly => {
+                return Err(format!(
+                    "Primitive polynomial {:#x} is not of degree {}",
+                    poly, m
+                ))
+            }
+        };
+        let order = if swap_bits {
+            BitOrder::LsbFirst
+        } else {
+            BitOrder::MsbFirst
+        };
+        Ok(KernelBch {
+            m: m,
+            t: t,
+            prim_poly: prim_poly,
+            codec: ByteCodec::primitive(m, t, prim_poly, order)?,
+        })
+    }
+
+    pub fn m(&self) -> u32 {
+        self.m
+    }
+
+    pub fn t(&self) -> i32 {
+        self.t
+    }
+
+    pub fn prim_poly(&self) -> u32 {
+        self.prim_poly
+    }
+
+    /// Degree of the generator polynomial.
+    pub fn ecc_bits(&self) -> usize {
+        let code = self.codec.code();
+        (code.n - code.k) as usize
+    }
+
+    /// Bytes of the ECC, DIV_ROUND_UP(m * t, 8) as in bch_init().
+    pub fn ecc_bytes(&self) -> usize {
+        (self.m as usize * self.t as usize).div_ceil(8)
+    }
+
+    /// Largest data buffer protected by one ECC.
+    pub fn max_data_len(&self) -> usize {
+        self.codec.code().k as usize / 8
+    }
+
+    /// ECC of data, as bch_encode() on a zeroed ECC buffer.
+    pub fn encode(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut ecc = self.codec.parity(data)?;
+        ecc.resize(self.ecc_bytes(), 0);
+        Ok(ecc)
+    }
+
+    /// Error locations from the received and the calculated ECC of len
+    /// data bytes, as decode_bch() without data. The locations are in
+    /// increasing order, an Err stands for -EINVAL or -EBADMSG.
+    pub fn decode(&self, len: usize, recv_ecc: &[u8], calc_ecc: &[u8]) -> Result<Vec<u32>, String> {
+        if len > self.max_data_len() {
+            return Err(format!("Data of {} bytes is too long", len));
+        }
+        if recv_ecc.len() != self.ecc_bytes() || calc_ecc.len() != self.ecc_bytes() {
+            return Err(format!("ECC has to have {} bytes", self.ecc_bytes()));
+        }
+        // the ECC difference is the remainder of the error pattern, which
+        // is the error pattern itself when all data bits are right
+        let mut word = vec![0u8; len];
+        word.extend(
+            recv_ecc
+                .iter()
+                .zip(calc_ecc)
+                .take(self.codec.parity_bytes())
+                .map(|(recv, calc)| recv ^ calc),
+        );
+        let swap_bits = self.codec.order() == BitOrder::LsbFirst;
+        let mut errloc: Vec<u32> = self
+            .codec
+            .locate_errors(&word)?
+            .iter()
+            .map(|p| {
+                let p = *p as u32;
+                if swap_bits {
+                    p
+                } else {
+                    (p & !7) | (7 - (p & 7))
+                }
+            })
+            .collect();
+        errloc.sort();
+        Ok(errloc)
+    }
+
+    /// Error locations of data and its received ECC, as decode_bch() with
+    /// data.
+    pub fn decode_data(&self, data: &[u8], recv_ecc: &[u8]) -> Result<Vec<u32>, String> {
+        let calc_ecc = self.encode(data)?;
+        self.decode(data.len(), recv_ecc, &calc_ecc)
+    }
+
+    /// Flips the data bits of the error locations, as correct_bch().
+    /// Locations in the ECC are skipped.
+    pub fn correct(&self, data: &mut [u8], errloc: &[u32]) {
+        for location in errloc {
+            let byte = *location as usize / 8;
+            if byte < data.len() {
+                data[byte] ^= 1 << (location % 8);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    const VECTORS: &str = include_str!("../testdata/kernel_bch_vectors.txt");
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..(i + 2)], 16).unwrap())
+            .collect()
+    }
+
+    /// Kind of the vector, the kernel code and the remaining fields. Lines
+    /// of the same parameters share the code.
+    fn vectors() -> Vec<(String, Rc<KernelBch>, Vec<String>)> {
+        let mut codes: Vec<(String, Rc<KernelBch>)> = Vec::new();
+        let mut vectors = Vec::new();
+        for line in VECTORS.lines() {
+            if line.starts_with('#') || line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let parameters = fields[1..5].join(" ");
+            let bch = match codes.iter().find(|(key, _)| *key == parameters) {
+                Some((_, bch)) => bch.clone(),
+                None => {
+                    let bch = Rc::new(
+                        KernelBch::new(
+                            fields[1].parse().unwrap(),
+                            fields[2].parse().unwrap(),
+                            u32::from_str_radix(&fields[3][2..], 16).unwrap(),
+                            fields[4] == "1",
+                        )
+                        .unwrap(),
+                    );
+                    codes.push((parameters, bch.clone()));
+                    bch
+                }
+            };
+            let rest = fields[5..].iter().map(|field| field.to_string()).collect();
+            vectors.push((fields[0].to_owned(), bch, rest));
+        }
+        vectors
+    }
+
+    #[test]
+    fn new_test() {
+        let bch = KernelBch::new(13, 4, 0, false).unwrap();
+        assert_eq!(bch.prim_poly(), 0x201b);
+        assert_eq!((bch.ecc_bits(), bch.ecc_bytes()), (52, 7));
+        assert_eq!(bch.max_data_len(), 1017);
+        // the generator of degree 45 is shorter than m * t = 54 bits
+        let bch = KernelBch::new(6, 9, 0, false).unwrap();
+        assert_eq!((bch.ecc_bits(), bch.ecc_bytes()), (45, 7));
+        assert_eq!(bch.max_data_len(), 2);
+        assert!(KernelBch::new(4, 2, 0, false).is_err());
+        assert!(KernelBch::new(5, 7, 0, false).is_err());
+        assert!(KernelBch::new(8, 4, 0x25, false).is_err());
+    }
+
+    #[test]
+    fn encode_vectors_test() {
+        let mut count = 0;
+        for (kind, bch, fields) in vectors() {
+            if kind == "encode" {
+                let ecc = bch.encode(&from_hex(&fields[0])).unwrap();
+                assert_eq!(ecc, from_hex(&fields[1]), "m: {}, t: {}", bch.m(), bch.t());
+                count += 1;
+            }
+        }
+        assert_eq!(count, 19);
+    }
+
+    #[test]
+    fn decode_vectors_test() {
+        let mut count = 0;
+        for (kind, bch, fields) in vectors() {
+            if kind != "decode" {
+                continue;
+            }
+            let data = from_hex(&fields[0]);
+            let mut received = from_hex(&fields[1]);
+            let recv_ecc = from_hex(&fields[2]);
+            let calc_ecc = from_hex(&fields[3]);
+            let errloc: Vec<u32> = fields[4].split(',').map(|l| l.parse().unwrap()).collect();
+
+            assert_eq!(bch.encode(&received).unwrap(), calc_ecc);
+            assert_eq!(
+                bch.decode(received.len(), &recv_ecc, &calc_ecc),
+                Ok(errloc.clone())
+            );
+            assert_eq!(bch.decode_data(&received, &recv_ecc), Ok(errloc.clone()));
+            bch.correct(&mut received, &errloc);
+            assert_eq!(received, data, "m: {}, t: {}", bch.m(), bch.t());
+            count += 1;
+        }
+        assert_eq!(count, 22);
+    }
+
+    #[test]
+    fn decode_failures_test() {
+        let bch = KernelBch::new(8, 2, 0, false).unwrap();
+        let data = [0x55u8; 8];
+        let ecc = bch.encode(&data).unwrap();
+        assert_eq!(bch.decode_data(&data, &ecc), Ok(vec![]));
+        assert!(bch.decode(bch.max_data_len() + 1, &ecc, &ecc).is_err());
+        assert!(bch.decode(8, &ecc[1..], &ecc[1..]).is_err());
+
+        let mut received = data;
+        received[0] ^= 0x07;
+        assert!(bch.decode_data(&received, &ecc).is_err());
+    }
+}
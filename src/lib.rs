@@ -0,0 +1,42 @@
+//! Binary BCH codes: construction, encoding, decoding and the tools around
+//! them. The bch binary is a command line front end of the cli module.
+
+extern crate bitvec;
+extern crate env_logger;
+extern crate itertools;
+extern crate log;
+extern crate rand;
+
+// const POWER: usize = 9;
+// const TWO_TO_POWER: usize = 512;
+
+pub mod bch_bitvec;
+pub mod bch_code;
+pub mod benchmark;
+pub mod berlekamp_decoder;
+pub mod bounds;
+pub mod byte_codec;
+pub mod channel;
+pub mod cli;
+pub mod common;
+pub mod container;
+pub mod decoder;
+pub mod design;
+pub mod dual;
+pub mod encoder;
+pub mod extended;
+pub mod forney;
+pub mod framing;
+pub mod galois_field;
+pub mod kernel_bch;
+pub mod matrices;
+pub mod nand;
+pub mod nonbinary_bch;
+pub mod reed_solomon;
+pub mod rng;
+pub mod simple_decoder;
+pub mod simulation;
+pub mod stream;
+mod tests;
+pub mod weight_enumerator;
+mod mycoder_tests;
@@ -1,23 +1,15 @@
-#![feature(vec_resize_default)]
-extern crate bitvec;
-extern crate env_logger;
-extern crate itertools;
-extern crate log;
-extern crate rand;
+use bch::cli;
 
-// const POWER: usize = 9;
-// const TWO_TO_POWER: usize = 512;
-
-mod bch_bitvec;
-mod berlekamp_decoder;
-mod common;
-mod decoder;
-mod encoder;
-mod simple_decoder;
-mod tests;
-mod mycoder_tests;
-
-fn main() {}
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::run(&args, &mut std::io::stdout()) {
+        Ok(code) => std::process::exit(code),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        }
+    }
+}
 
 //TODO
 // add comments to doc, maybe
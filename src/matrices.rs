@@ -0,0 +1,225 @@
+use crate::bch_bitvec::*;
+use crate::bch_code::BchCode;
+use bitvec::prelude::*;
+
+/// Systematic k x n generator matrix matching `Encoder`: the message takes
+/// the first k columns, row i ends with the remainder of x^(n-1-i) by g(x).
+pub fn generator_matrix(n: i32, k: i32, gen_poly: &BitVec) -> Vec<BitVec> {
+    let control_len = (n - k) as usize;
+    (0..k as usize)
+        .map(|i| {
+            let mut x_power = bitvec![1];
+            x_power.extend(bitvec![0; n as usize - 1 - i]);
+            let remainder = x_power.remainder_divide(gen_poly).unwrap();
+
+            let mut row = bitvec![0; k as usize];
+            row.set(i, true);
+            row.extend(bitvec![0; control_len - remainder.len()]);
+            row.extend(remainder);
+            row
+        })
+        .collect()
+}
+
+/// Systematic (n-k) x n parity-check matrix [P^T | I] for G = [I | P].
+pub fn parity_check_matrix(n: i32, k: i32, gen_poly: &BitVec) -> Vec<BitVec> {
+    let generator = generator_matrix(n, k, gen_poly);
+    (0..(n - k) as usize)
+        .map(|j| {
+            let mut row: BitVec = generator
+                .iter()
+                .map(|g_row| g_row[k as usize + j])
+                .collect();
+            row.extend(bitvec![0; (n - k) as usize]);
+            row.set(k as usize + j, true);
+            row
+        })
+        .collect()
+}
+
+/// 2t x n parity-check matrix over GF(2^m) with alpha powers as entries.
+/// Row i holds the powers of the (i+1)-th consecutive root, column j the
+/// position of x^(n-1-j), so H * c^T is the syndrome vector.
+pub fn field_parity_check_matrix(code: &BchCode) -> Vec<Vec<i32>> {
+    let (_, field) = code.gen_poly_and_field();
+    let step = code.field_order() / code.cycle_len;
+    (0..2 * code.t)
+        .map(|i| {
+            let root = (code.first_root + i) * step;
+            (0..code.n)
+                .map(|j| field.alpha_mod_order(root * (code.n - 1 - j)))
+                .collect()
+        })
+        .collect()
+}
+
+/// One row per line, entries separated by spaces.
+pub fn to_dense_text(matrix: &Vec<BitVec>) -> String {
+    matrix
+        .iter()
+        .map(|row| {
+            let entries: Vec<&str> = row
+                .iter()
+                .by_vals()
+                .map(|bit| if bit { "1" } else { "0" })
+                .collect();
+            entries.join(" ") + "\n"
+        })
+        .collect()
+}
+
+/// MacKay's alist format: sizes, maximum and per column/row weights, then
+/// 1-based row indices of every column and column indices of every row,
+/// padded with zeros.
+pub fn to_alist(matrix: &Vec<BitVec>) -> String {
+    let rows = matrix.len();
+    let cols = if rows == 0 { 0 } else { matrix[0].len() };
+    let row_entries: Vec<Vec<usize>> = matrix
+        .iter()
+        .map(|row| (0..cols).filter(|j| row[*j]).map(|j| j + 1).collect())
+        .collect();
+    let col_entries: Vec<Vec<usize>> = (0..cols)
+        .map(|j| (0..rows).filter(|i| matrix[*i][j]).map(|i| i + 1).collect())
+        .collect();
+    let max_col_weight = col_entries.iter().map(|col| col.len()).max().unwrap_or(0);
+    let max_row_weight = row_entries.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    let mut alist = format!("{} {}\n{} {}\n", cols, rows, max_col_weight, max_row_weight);
+    alist += &join_line(col_entries.iter().map(|col| col.len()));
+    alist += &join_line(row_entries.iter().map(|row| row.len()));
+    for col in col_entries.iter() {
+        alist += &join_line(padded(col, max_col_weight));
+    }
+    for row in row_entries.iter() {
+        alist += &join_line(padded(row, max_row_weight));
+    }
+    alist
+}
+
+/// Matrix Market coordinate pattern format with 1-based (row, column) pairs.
+pub fn to_sparse(matrix: &Vec<BitVec>) -> String {
+    let rows = matrix.len();
+    let cols = if rows == 0 { 0 } else { matrix[0].len() };
+    let entries: Vec<(usize, usize)> = (0..rows)
+        .flat_map(|i| {
+            (0..cols)
+                .filter(move |j| matrix[i][*j])
+                .map(move |j| (i + 1, j + 1))
+        })
+        .collect();
+
+    let mut sparse = "%%MatrixMarket matrix coordinate pattern general\n".to_owned();
+    sparse += &format!("{} {} {}\n", rows, cols, entries.len());
+    for (i, j) in entries {
+        sparse += &format!("{} {}\n", i, j);
+    }
+    sparse
+}
+
+fn padded(entries: &Vec<usize>, len: usize) -> impl Iterator<Item = usize> + '_ {
+    entries
+        .iter()
+        .cloned()
+        .chain(std::iter::repeat(0))
+        .take(len)
+}
+
+fn join_line<I: Iterator<Item = usize>>(values: I) -> String {
+    let values: Vec<String> = values.map(|value| value.to_string()).collect();
+    values.join(" ") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::Encoder;
+
+    fn get_hamming_gen_poly() -> BitVec {
+        bitvec![1, 0, 1, 1]
+    }
+
+    #[test]
+    fn generator_matrix_test() {
+        let result = generator_matrix(7, 4, &get_hamming_gen_poly());
+        let expected = vec![
+            bitvec![1, 0, 0, 0, 1, 0, 1],
+            bitvec![0, 1, 0, 0, 1, 1, 1],
+            bitvec![0, 0, 1, 0, 1, 1, 0],
+            bitvec![0, 0, 0, 1, 0, 1, 1],
+        ];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn generator_matrix_rows_are_encoded_unit_messages_test() {
+        let prime_poly = bitvec![1, 0, 0, 1, 1];
+        let encoder = Encoder::new(15, 7, 2, &prime_poly);
+        let code = BchCode::new(15, 7, 2, &prime_poly);
+        let generator = generator_matrix(15, 7, &code.gen_poly());
+        for (i, row) in generator.iter().enumerate() {
+            let mut msg = bitvec![0; 7];
+            msg.set(i, true);
+            assert_eq!(encoder.encode(&msg).unwrap(), *row);
+        }
+    }
+
+    #[test]
+    fn parity_check_matrix_test() {
+        let result = parity_check_matrix(7, 4, &get_hamming_gen_poly());
+        let expected = vec![
+            bitvec![1, 1, 1, 0, 1, 0, 0],
+            bitvec![0, 1, 1, 1, 0, 1, 0],
+            bitvec![1, 1, 0, 1, 0, 0, 1],
+        ];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn field_parity_check_matrix_test() {
+        let codes = vec![
+            BchCode::new(15, 7, 2, &bitvec![1, 0, 0, 1, 1]),
+            BchCode::new(25, 10, 3, &bitvec![1, 0, 0, 1, 0, 1]),
+            BchCode::new_non_primitive(21, 12, 2, &bitvec![1, 0, 0, 0, 0, 1, 1]),
+            BchCode::new(31, 11, 3, &bitvec![1, 0, 0, 1, 0, 1]).with_first_root(3),
+        ];
+        for code in codes {
+            let (gen_poly, field) = code.gen_poly_and_field();
+            let field_check = field_parity_check_matrix(&code);
+            assert_eq!(field_check.len(), 2 * code.t as usize);
+            for codeword in generator_matrix(code.n, code.k, &gen_poly) {
+                for row in field_check.iter() {
+                    let syndrome = row
+                        .iter()
+                        .zip(codeword.iter().by_vals())
+                        .filter(|(_, bit)| *bit)
+                        .fold(-1, |sum, (alpha, _)| field.add_alphas(sum, *alpha));
+                    assert_eq!(syndrome, -1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn to_dense_text_test() {
+        let result = to_dense_text(&parity_check_matrix(7, 4, &get_hamming_gen_poly()));
+        assert_eq!(result, "1 1 1 0 1 0 0\n0 1 1 1 0 1 0\n1 1 0 1 0 0 1\n");
+    }
+
+    #[test]
+    fn to_alist_test() {
+        let result = to_alist(&parity_check_matrix(7, 4, &get_hamming_gen_poly()));
+        let expected = "7 3\n3 4\n2 3 2 2 1 1 1\n4 4 4\n\
+                        1 3 0\n1 2 3\n1 2 0\n2 3 0\n1 0 0\n2 0 0\n3 0 0\n\
+                        1 2 3 5\n2 3 4 6\n1 2 4 7\n";
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn to_sparse_test() {
+        let matrix = vec![bitvec![1, 0, 1], bitvec![0, 1, 0]];
+        let result = to_sparse(&matrix);
+        let expected = "%%MatrixMarket matrix coordinate pattern general\n\
+                        2 3 3\n1 1\n1 3\n2 2\n";
+        assert_eq!(result, expected);
+    }
+}
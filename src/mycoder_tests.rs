@@ -5,12 +5,15 @@ mod tests {
     use crate::decoder::Decoder;
     use crate::encoder::Encoder;
     use crate::simple_decoder::SimpleDecoder;
-    use crate::common::get_random_places;
-    use bitvec::*;
+    use crate::channel::FixedErrorsChannel;
+    use crate::simulation::{simulate_point, SimulationConfig};
+    use crate::common::get_random_msg;
+    use crate::rng::{seed_from_env, test_rng, SEED_VARIABLE};
+    use bitvec::prelude::*;
     use rand::prelude::*;
+    use rand::rngs::StdRng;
     use std::sync::mpsc;
     use std::thread;
-    use std::time::{Duration, Instant};
 
     static n: i32 = 255;
     static k: i32 = 123;
@@ -55,10 +58,7 @@ mod tests {
     // }
 
     fn generate_random_msg_and_code_word() -> (BitVec, BitVec) {
-        let mut msg = BitVec::new();
-        for _i in 0..k {
-            msg.push(rand::random());
-        }
+        let msg = get_random_msg(k as usize, &mut test_rng());
         // let encoder = get_encoder();
         let encoder = get_ready_encoder();
         let word = encoder.encode(&msg).unwrap();
@@ -71,23 +71,13 @@ mod tests {
         let (msg, code_word) = generate_random_msg_and_code_word(); //TODO save coders to file
         let simple = get_ready_simple_decoder();
 
-        let mut time: Duration = Duration::new(0, 0);
         for i in 0..255 {
             println!("i: {}", i);
             let mut code_word_clone = code_word.clone();
             code_word_clone.inverse_nth(i);
-            let start = Instant::now();
             let (decoded, _) = simple.decode(&code_word_clone).unwrap();
-            let stop = Instant::now();
-            time += stop.duration_since(start);
             assert_eq!(msg, decoded);
         }
-
-        println!("Decoding took: {:?}", time);
-        println!(
-            "Mean time {:?}",
-            time / code_word.len() as u32
-        );
     }
 
     #[test]
@@ -96,7 +86,6 @@ mod tests {
         let (msg, code_word) = generate_random_msg_and_code_word(); //TODO save coders to file
         let simple = get_ready_simple_decoder();
 
-        let mut time: Duration = Duration::new(0, 0);
         for i in 0..255 {
             println!("i: {}", i);
             let mut code_word_clone = code_word.clone();
@@ -106,18 +95,9 @@ mod tests {
             } else {
                 code_word_clone.inverse_nth(0);
             }
-            let start = Instant::now();
             let (decoded, _) = simple.decode(&code_word_clone).unwrap();
-            let stop = Instant::now();
-            time += stop.duration_since(start);
             assert_eq!(msg, decoded);
         }
-        
-        println!("Decoding took: {:?}", time);
-        println!(
-            "Mean time {:?}",
-            time / code_word.len() as u32
-        );
     }
 
     #[test]
@@ -126,7 +106,6 @@ mod tests {
         let (msg, code_word) = generate_random_msg_and_code_word(); //TODO save coders to file
         let simple = get_ready_simple_decoder();
 
-        let mut time: Duration = Duration::new(0, 0);
         for i in 0..255 {
             println!("i: {}", i);
             let mut code_word_clone = code_word.clone();
@@ -143,18 +122,9 @@ mod tests {
                     code_word_clone.inverse_nth(j);
                 }
             }
-            let start = Instant::now();
             let (decoded, _) = simple.decode(&code_word_clone).unwrap();
-            let stop = Instant::now();
-            time += stop.duration_since(start);
             assert_eq!(msg, decoded);
         }
-        
-        println!("Decoding took: {:?}", time);
-        println!(
-            "Mean time {:?}",
-            time / code_word.len() as u32
-        );
     }
 
     #[test]
@@ -169,7 +139,6 @@ mod tests {
         let (msg, code_word) = generate_random_msg_and_code_word(); //TODO save coders to file
         let simple = get_ready_simple_decoder();
 
-        let mut time: Duration = Duration::new(0, 0);
         let mut ok = 0;
         let mut fail = 0;
         for i in 1..255 {
@@ -178,10 +147,7 @@ mod tests {
             code_word_clone.inverse_nth(0);
             code_word_clone.inverse_nth(i);
 
-            let start = Instant::now();
             let result = simple.decode(&code_word_clone);
-            let stop = Instant::now();
-            time += stop.duration_since(start);
             match result {
                 Ok((decoded, _)) => {
                     if decoded == msg {
@@ -193,12 +159,6 @@ mod tests {
                 }
             }
         }
-        
-        println!("Decoding took: {:?}", time);
-        println!(
-            "Mean time {:?}",
-            time / 254 as u32
-        );
         println!("ok: {}, fail: {}", ok, fail);
     }
 
@@ -239,40 +199,24 @@ mod tests {
     }
 
     fn n_itr_random(e: i32, itr: i32) {
-        let (msg, code_word) = generate_random_msg_and_code_word(); //TODO save coders to file
+        let encoder = get_ready_encoder();
         let simple = get_ready_simple_decoder();
-
-        let mut time: Duration = Duration::new(0, 0);
-        let mut ok = 0;
-        let mut fail = 0;
-        for i in 0..itr {
-            println!("i: {}", i);
-            let mut code_word_clone = code_word.clone();
-            let places = get_random_places(e, 255);
-            for p in places {
-                code_word_clone.inverse_nth(p);
-            }
-            let start = Instant::now();
-            let result = simple.decode(&code_word_clone);
-            let stop = Instant::now();
-            time += stop.duration_since(start);
-            match result {
-                Ok((decoded, _)) => {
-                    if decoded == msg {
-                        ok += 1;
-                    }
-                },
-                Err(_) => {
-                    fail += 1;
-                }
-            }
-        }
-        
-        println!("Decoding took: {:?}", time);
+        let config = SimulationConfig::new(itr as u64, itr as u64, seed_from_env().unwrap());
+        println!("{}={}", SEED_VARIABLE, config.seed);
+        let mut channel = FixedErrorsChannel::new(e as usize);
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let result = simulate_point(
+            &encoder,
+            &simple,
+            k as usize,
+            &mut channel,
+            e as f64,
+            &config,
+            &mut rng,
+        );
         println!(
-            "Mean time {:?}",
-            time / itr as u32
+            "frames: {}, frame errors: {}, decoder failures: {}",
+            result.frames, result.frame_errors, result.decoder_failures
         );
-        println!("ok: {}, fail: {}", ok, fail);
     }
 }
@@ -0,0 +1,312 @@
+use crate::framing::BlockStats;
+use crate::kernel_bch::KernelBch;
+use std::ops::Range;
+
+/// Bytes at the start of the spare area kept for the bad block marker.
+pub const BAD_BLOCK_MARKER_LEN: usize = 2;
+
+/// ECC layout of a NAND page protected sector by sector with lib/bch
+/// codes, as the kernel nand_bch does. The ECC of all sectors is packed at
+/// the end of the spare area. It is stored xored with the complement of
+/// the ECC of an erased sector, so that an erased page of 0xff bytes reads
+/// as a valid code word.
+pub struct NandLayout {
+    page_size: usize,
+    sector_size: usize,
+    spare_size: usize,
+    bch: KernelBch,
+    ecc_mask: Vec<u8>,
+}
+
+/// Corrected data of a page and the outcome of every sector, bitflips
+/// being the corrected_bits of the sector.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PageDecode {
+    pub data: Vec<u8>,
+    pub sectors: Vec<BlockStats>,
+}
+
+impl PageDecode {
+    /// Most bitflips corrected in one sector, what wear levelling looks at.
+    pub fn max_bitflips(&self) -> usize {
+        self.sectors
+            .iter()
+            .map(|sector| sector.corrected_bits)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn uncorrectable_sectors(&self) -> Vec<usize> {
+        self.sectors
+            .iter()
+            .filter(|sector| sector.uncorrectable)
+            .map(|sector| sector.block)
+            .collect()
+    }
+}
+
+impl NandLayout {
+    /// Layout correcting strength bitflips per sector. As in nand_bch, m is
+    /// the smallest with 2^m > 8 * sector_size, increased while the ECC
+    /// does not fit the code.
+    pub fn new(
+        page_size: usize,
+        sector_size: usize,
+        spare_size: usize,
+        strength: i32,
+    ) -> Result<NandLayout, String> {
+        if sector_size == 0 || page_size == 0 || page_size % sector_size != 0 {
+            return Err(format!(
+                "Page size {} is not a multiple of the sector size {}",
+                page_size, sector_size
+            ));
+        }
+        let data_bits = 8 * sector_size as u64;
+        let mut m = 64 - (1 + data_bits).leading_zeros();
+        while (1u64 << m) - 1 < data_bits + m as u64 * strength.max(0) as u64 {
+            m += 1;
+        }
+        let bch = KernelBch::new(m.max(5), strength, 0, false)?;
+        if bch.max_data_len() < sector_size {
+            return Err(format!(
+                "Sector of {} bytes does not fit BCH m: {}, t: {}",
+                sector_size,
+                bch.m(),
+                strength
+            ));
+        }
+
+        let ecc_len = page_size / sector_size * bch.ecc_bytes();
+        if ecc_len + BAD_BLOCK_MARKER_LEN > spare_size {
+            return Err(format!(
+                "Spare area of {} bytes cannot hold {} ECC bytes",
+                spare_size, ecc_len
+            ));
+        }
+        let ecc_mask = bch
+            .encode(&vec![0xff; sector_size])?
+            .iter()
+            .map(|byte| !byte)
+            .collect();
+        Ok(NandLayout {
+            page_size: page_size,
+            sector_size: sector_size,
+            spare_size: spare_size,
+            bch: bch,
+            ecc_mask: ecc_mask,
+        })
+    }
+
+    pub fn page_size(&self) -> usize {
+        self.page_size
+    }
+
+    pub fn sector_size(&self) -> usize {
+        self.sector_size
+    }
+
+    pub fn spare_size(&self) -> usize {
+        self.spare_size
+    }
+
+    pub fn m(&self) -> u32 {
+        self.bch.m()
+    }
+
+    pub fn strength(&self) -> i32 {
+        self.bch.t()
+    }
+
+    pub fn sectors(&self) -> usize {
+        self.page_size / self.sector_size
+    }
+
+    pub fn ecc_bytes(&self) -> usize {
+        self.bch.ecc_bytes()
+    }
+
+    /// Spare area bytes holding the ECC of the sector.
+    pub fn ecc_range(&self, sector: usize) -> Range<usize> {
+        let start = self.spare_size - self.sectors() * self.ecc_bytes() + sector * self.ecc_bytes();
+        start..(start + self.ecc_bytes())
+    }
+
+    /// Spare area bytes left to the user, after the bad block marker.
+    pub fn free_range(&self) -> Range<usize> {
+        BAD_BLOCK_MARKER_LEN..self.ecc_range(0).start
+    }
+
+    fn sector_ecc(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        Ok(self
+            .bch
+            .encode(data)?
+            .iter()
+            .zip(&self.ecc_mask)
+            .map(|(ecc, mask)| ecc ^ mask)
+            .collect())
+    }
+
+    /// Writes the ECC of every sector of data into the spare area, other
+    /// spare bytes are left as they are.
+    pub fn write_ecc(&self, data: &[u8], spare: &mut [u8]) -> Result<(), String> {
+        if data.len() != self.page_size || spare.len() != self.spare_size {
+            return Err(format!(
+                "Expected {} data and {} spare bytes, got {} and {}",
+                self.page_size,
+                self.spare_size,
+                data.len(),
+                spare.len()
+            ));
+        }
+        for (sector, chunk) in data.chunks(self.sector_size).enumerate() {
+            let ecc = self.sector_ecc(chunk)?;
+            spare[self.ecc_range(sector)].copy_from_slice(&ecc);
+        }
+        Ok(())
+    }
+
+    /// Raw page of data followed by a spare area of 0xff and the ECC.
+    pub fn encode_page(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        let mut spare = vec![0xff; self.spare_size];
+        self.write_ecc(data, &mut spare)?;
+        let mut raw = data.to_vec();
+        raw.extend(spare);
+        Ok(raw)
+    }
+
+    /// Data of a raw page, data followed by spare area. Uncorrectable
+    /// sectors are returned as read.
+    pub fn decode_page(&self, raw: &[u8]) -> Result<PageDecode, String> {
+        if raw.len() != self.page_size + self.spare_size {
+            return Err(format!(
+                "Raw page has to have {} bytes, got {}",
+                self.page_size + self.spare_size,
+                raw.len()
+            ));
+        }
+        let (data, spare) = raw.split_at(self.page_size);
+        let mut data = data.to_vec();
+        let mut sectors = Vec::new();
+        for sector in 0..self.sectors() {
+            let chunk = &mut data[(sector * self.sector_size)..((sector + 1) * self.sector_size)];
+            let recv_ecc = &spare[self.ecc_range(sector)];
+            let calc_ecc = self.sector_ecc(chunk)?;
+            let stats = match self.bch.decode(self.sector_size, recv_ecc, &calc_ecc) {
+                Ok(errloc) => {
+                    self.bch.correct(chunk, &errloc);
+                    BlockStats {
+                        block: sector,
+                        corrected_bits: errloc.len(),
+                        uncorrectable: false,
+                    }
+                }
+                Err(_) => BlockStats {
+                    block: sector,
+                    corrected_bits: 0,
+                    uncorrectable: true,
+                },
+            };
+            sectors.push(stats);
+        }
+        Ok(PageDecode {
+            data: data,
+            sectors: sectors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flip(raw: &mut [u8], bit: usize) {
+        raw[bit / 8] ^= 0x80 >> (bit % 8);
+    }
+
+    #[test]
+    fn layout_test() {
+        // 2048 + 64 page, 4 bit correction per 512 bytes as in nand_bch
+        let layout = NandLayout::new(2048, 512, 64, 4).unwrap();
+        assert_eq!((layout.page_size(), layout.sector_size()), (2048, 512));
+        assert_eq!(layout.spare_size(), 64);
+        assert_eq!((layout.m(), layout.strength()), (13, 4));
+        assert_eq!((layout.sectors(), layout.ecc_bytes()), (4, 7));
+        assert_eq!(layout.ecc_range(0), 36..43);
+        assert_eq!(layout.ecc_range(3), 57..64);
+        assert_eq!(layout.free_range(), 2..36);
+
+        let layout = NandLayout::new(4096, 1024, 224, 24).unwrap();
+        assert_eq!((layout.m(), layout.ecc_bytes()), (14, 42));
+        assert_eq!(layout.ecc_range(0), 56..98);
+    }
+
+    #[test]
+    fn padded_ecc_layout_test() {
+        // m = 6, t = 9: a generator of degree 45 in 7 ECC bytes as in nand_bch
+        let layout = NandLayout::new(4, 1, 30, 9).unwrap();
+        assert_eq!((layout.m(), layout.ecc_bytes()), (6, 7));
+        assert_eq!(layout.ecc_range(0), 2..9);
+        assert_eq!(layout.ecc_range(3), 23..30);
+        assert_eq!(layout.free_range(), 2..2);
+
+        let data = [0x12, 0x34, 0x56, 0x78];
+        let mut raw = layout.encode_page(&data).unwrap();
+        // nine flips in sector 0, one of them in its data
+        flip(&mut raw, 3);
+        for bit in &[0, 9, 10, 11, 12, 13, 14, 44] {
+            flip(&mut raw, (4 + layout.ecc_range(0).start) * 8 + bit);
+        }
+        let decoded = layout.decode_page(&raw).unwrap();
+        assert_eq!(decoded.data, data);
+        assert_eq!(decoded.max_bitflips(), 9);
+    }
+
+    #[test]
+    fn layout_fails_test() {
+        assert!(NandLayout::new(2048, 500, 64, 4).is_err());
+        assert!(NandLayout::new(2048, 512, 64, 10).is_err());
+        assert!(NandLayout::new(2048, 512, 64, 0).is_err());
+    }
+
+    #[test]
+    fn erased_page_test() {
+        let layout = NandLayout::new(1024, 512, 32, 4).unwrap();
+        let raw = vec![0xff; 1024 + 32];
+        let data = vec![0xff; 1024];
+        assert_eq!(layout.encode_page(&data).unwrap(), raw);
+        let decoded = layout.decode_page(&raw).unwrap();
+        assert_eq!(decoded.data, data);
+        assert_eq!(decoded.max_bitflips(), 0);
+    }
+
+    #[test]
+    fn decode_page_test() {
+        let layout = NandLayout::new(1024, 512, 32, 4).unwrap();
+        let data: Vec<u8> = (0..1024).map(|i| (i * 37 % 256) as u8).collect();
+        let mut raw = layout.encode_page(&data).unwrap();
+        assert_eq!(&raw[..1024], &data[..]);
+        assert_eq!(layout.decode_page(&raw).unwrap().data, data);
+
+        // three flips in sector 0, one of them in its ECC, none in sector 1
+        let ecc_bit = (1024 + layout.ecc_range(0).start) * 8 + 5;
+        for bit in &[0, 4095, ecc_bit] {
+            flip(&mut raw, *bit);
+        }
+        let decoded = layout.decode_page(&raw).unwrap();
+        assert_eq!(decoded.data, data);
+        assert_eq!(decoded.sectors[0].corrected_bits, 3);
+        assert_eq!(decoded.sectors[1].corrected_bits, 0);
+        assert_eq!(decoded.max_bitflips(), 3);
+        assert!(decoded.uncorrectable_sectors().is_empty());
+
+        // five flips in sector 1 are beyond the strength
+        for bit in 0..5 {
+            flip(&mut raw, 4096 + bit * 100);
+        }
+        let decoded = layout.decode_page(&raw).unwrap();
+        assert_eq!(&decoded.data[..512], &data[..512]);
+        assert_eq!(&decoded.data[512..], &raw[512..1024]);
+        assert_eq!(decoded.uncorrectable_sectors(), vec![1]);
+        assert!(layout.decode_page(&raw[1..]).is_err());
+    }
+}
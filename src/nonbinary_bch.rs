@@ -0,0 +1,379 @@
+use crate::berlekamp_decoder;
+use crate::common;
+use crate::forney;
+use crate::galois_field::GaloisField;
+use bitvec::prelude::*;
+
+/// GF(2^s) symbols embedded in the extension field GF(2^(s*m)) where the
+/// roots of a non-binary BCH code live.
+#[derive(Clone)]
+struct SymbolField {
+    field: GaloisField,
+    symbol_field: GaloisField,
+    // alpha power of the root of the symbol prime polynomial
+    embedding: i32,
+    // symbol value of every alpha power, -1 outside of the subfield
+    alpha_symbols: Vec<i32>,
+}
+
+impl SymbolField {
+    fn new(symbol_prime_poly: &BitVec, prime_poly: &BitVec) -> SymbolField {
+        let field = GaloisField::new(prime_poly);
+        let symbol_field = GaloisField::new(symbol_prime_poly);
+        if field.order() % symbol_field.order() != 0 {
+            panic!(
+                "Bad symbol field. GF(2^{}) is not a subfield of GF(2^{})",
+                symbol_field.m(),
+                field.m()
+            );
+        }
+
+        // the subfield consists of powers of alpha^step, pick one that is
+        // a root of the symbol prime polynomial
+        let step = field.order() / symbol_field.order();
+        let symbol_prime_alphas: Vec<i32> = symbol_prime_poly
+            .iter()
+            .by_vals()
+            .map(|bit| if bit { 0 } else { -1 })
+            .collect();
+        let embedding = (1..(symbol_field.order() + 1))
+            .map(|j| field.alpha_mod_order(j * step))
+            .find(|alpha| field.evaluate_poly(&symbol_prime_alphas, *alpha) == -1)
+            .expect("Symbol prime polynomial has no root in the extension field");
+
+        let mut alpha_symbols = vec![-1; field.order() as usize];
+        for i in 0..symbol_field.order() {
+            alpha_symbols[field.alpha_mod_order(i * embedding) as usize] =
+                symbol_field.alpha_to_value(i) as i32;
+        }
+
+        SymbolField {
+            field: field,
+            symbol_field: symbol_field,
+            embedding: embedding,
+            alpha_symbols: alpha_symbols,
+        }
+    }
+
+    fn q(&self) -> i32 {
+        self.symbol_field.order() + 1
+    }
+
+    fn symbol_to_alpha(&self, symbol: u8) -> Result<i32, String> {
+        if symbol as i32 >= self.q() {
+            return Err(format!("Symbol {} is out of the field!", symbol));
+        }
+        let symbol_alpha = self.symbol_field.value_to_alpha(symbol as u32);
+        if symbol_alpha == -1 {
+            return Ok(-1);
+        }
+        Ok(self.field.alpha_mod_order(symbol_alpha * self.embedding))
+    }
+
+    fn alpha_to_symbol(&self, alpha: i32) -> Option<u8> {
+        if alpha == -1 {
+            return Some(0);
+        }
+        match self.alpha_symbols[self.field.alpha_mod_order(alpha) as usize] {
+            -1 => None,
+            symbol => Some(symbol as u8),
+        }
+    }
+
+    fn symbols_to_poly(&self, symbols: &[u8]) -> Result<Vec<i32>, String> {
+        symbols
+            .iter()
+            .map(|symbol| self.symbol_to_alpha(*symbol))
+            .collect()
+    }
+
+    /// Generator with roots alpha^1..alpha^2t, the product of the minimal
+    /// polynomials over GF(q) given by the q-cyclotomic cosets.
+    fn gen_poly(&self, t: i32) -> Vec<i32> {
+        let cosets = common::get_consecutive_q_cosets(
+            1,
+            2 * t as u32,
+            self.field.order() as u32,
+            self.q() as u32,
+        );
+        cosets
+            .iter()
+            .flat_map(|coset| coset.iter())
+            .fold(vec![0], |gen_poly, alpha| {
+                self.field
+                    .multiply_polys(&gen_poly, &vec![0, *alpha as i32])
+            })
+    }
+}
+
+/// BCH code over GF(q), q = 2^s, of length n <= q^m - 1 correcting t symbol
+/// errors. Shorter n gives a shortened code. With s = 1 it is the binary code.
+#[derive(Clone)]
+pub struct NonBinaryEncoder {
+    n: i32,
+    k: i32,
+    symbols: SymbolField,
+    gen_poly: Vec<i32>,
+}
+
+impl NonBinaryEncoder {
+    pub fn new(
+        n: i32,
+        k: i32,
+        t: i32,
+        symbol_prime_poly: &BitVec,
+        prime_poly: &BitVec,
+    ) -> NonBinaryEncoder {
+        let symbols = SymbolField::new(symbol_prime_poly, prime_poly);
+        let gen_poly = symbols.gen_poly(t);
+        validate_nonbinary_params(n, k, &gen_poly, &symbols);
+        NonBinaryEncoder {
+            n: n,
+            k: k,
+            symbols: symbols,
+            gen_poly: gen_poly,
+        }
+    }
+
+    /// Generator polynomial coefficients as symbols, highest degree first.
+    pub fn gen_poly(&self) -> Vec<u8> {
+        self.gen_poly
+            .iter()
+            .map(|alpha| self.symbols.alpha_to_symbol(*alpha).unwrap())
+            .collect()
+    }
+
+    pub fn encode(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        if data.len() as i32 > self.k {
+            return Err("Encode: to long data!".to_owned());
+        }
+
+        let control_len = (self.n - self.k) as usize;
+        let mut data_poly = self.symbols.symbols_to_poly(data)?;
+        data_poly.extend(vec![-1; control_len]);
+
+        let remainder = self
+            .symbols
+            .field
+            .remainder_divide_polys(&data_poly, &self.gen_poly);
+        let mut encoded = data.to_vec();
+        encoded.extend(vec![0; control_len - remainder.len()]);
+        for alpha in remainder {
+            encoded.push(self.symbols.alpha_to_symbol(alpha).unwrap());
+        }
+
+        Ok(encoded)
+    }
+}
+
+#[derive(Clone)]
+pub struct NonBinaryDecoder {
+    n: i32,
+    k: i32,
+    t: i32,
+    symbols: SymbolField,
+}
+
+impl NonBinaryDecoder {
+    pub fn new(
+        n: i32,
+        k: i32,
+        t: i32,
+        symbol_prime_poly: &BitVec,
+        prime_poly: &BitVec,
+    ) -> NonBinaryDecoder {
+        let symbols = SymbolField::new(symbol_prime_poly, prime_poly);
+        validate_nonbinary_params(n, k, &symbols.gen_poly(t), &symbols);
+        NonBinaryDecoder {
+            n: n,
+            k: k,
+            t: t,
+            symbols: symbols,
+        }
+    }
+
+    /// Returns the decoded message and the corrected symbols as
+    /// (position in the received word, error value) pairs.
+    pub fn decode(&self, received: &[u8]) -> Result<(Vec<u8>, Vec<(usize, u8)>), String> {
+        let control_len = (self.n - self.k) as usize;
+        if received.len() > self.n as usize || received.len() < control_len {
+            return Err("Encoded data has wrong length!".to_owned());
+        }
+        let msg_len = received.len() - control_len;
+        let field = &self.symbols.field;
+
+        let received_poly = self.symbols.symbols_to_poly(received)?;
+        let syndroms_alphas: Vec<i32> = (1..(2 * self.t + 1))
+            .map(|j| field.evaluate_poly(&received_poly, j))
+            .collect();
+        if syndroms_alphas.iter().all(|syndrome| *syndrome == -1) {
+            return Ok((received[..msg_len].to_vec(), Vec::new()));
+        }
+
+        // S(2i) = S(i)^2 holds only for binary symbols, one syndrome per step
+        let (sigma, lu) = berlekamp_decoder::find_error_locator(field, &syndroms_alphas, 1);
+        let roots = field.find_roots(&sigma);
+        if lu > self.t || roots.len() != sigma.len() - 1 || roots.len() as i32 != lu {
+            return Err("Too many errors. Could not decode".to_owned());
+        }
+        let values = forney::error_values(field, &syndroms_alphas, &sigma, &roots, 1)?;
+
+        let mut corrected = received.to_vec();
+        let mut corrections = Vec::new();
+        for (root, value) in roots.iter().zip(values) {
+            let position = field.alpha_mod_order(-root) as usize;
+            if position >= received.len() {
+                return Err("Error located outside of the code word. Could not decode".to_owned());
+            }
+            let value = match self.symbols.alpha_to_symbol(value) {
+                Some(value) => value,
+                None => {
+                    return Err(
+                        "Error value outside of the symbol field. Could not decode".to_owned()
+                    )
+                }
+            };
+
+            let idx = received.len() - 1 - position;
+            corrected[idx] ^= value;
+            corrections.push((idx, value));
+        }
+        corrections.sort();
+
+        Ok((corrected[..msg_len].to_vec(), corrections))
+    }
+}
+
+fn validate_nonbinary_params(n: i32, k: i32, gen_poly: &Vec<i32>, symbols: &SymbolField) {
+    if symbols.symbol_field.m() > 8
+        || n > symbols.field.order()
+        || k <= 0
+        || n != k + gen_poly.len() as i32 - 1
+    {
+        panic!(
+            "Bad coder parameters. n: {}, k: {}, gen degree: {}",
+            n,
+            k,
+            gen_poly.len() - 1
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::Encoder;
+
+    fn get_gf4_symbols() -> BitVec {
+        bitvec![1, 1, 1]
+    }
+
+    fn get_gf16_prime_poly() -> BitVec {
+        bitvec![1, 0, 0, 1, 1]
+    }
+
+    #[test]
+    fn symbol_field_embedding_test() {
+        let symbols = SymbolField::new(&get_gf4_symbols(), &get_gf16_prime_poly());
+        assert_eq!(symbols.q(), 4);
+        for symbol in 0..4 {
+            let alpha = symbols.symbol_to_alpha(symbol).unwrap();
+            assert_eq!(symbols.alpha_to_symbol(alpha), Some(symbol));
+        }
+        // 2 * 3 = 1 in GF(4) with x^2 + x + 1
+        let product = symbols.field.multiply_alphas(
+            symbols.symbol_to_alpha(2).unwrap(),
+            symbols.symbol_to_alpha(3).unwrap(),
+        );
+        assert_eq!(symbols.alpha_to_symbol(product), Some(1));
+        assert_eq!(symbols.alpha_to_symbol(1), None);
+        assert!(symbols.symbol_to_alpha(4).is_err());
+    }
+
+    #[test]
+    fn gen_poly_divides_x_n_plus_1_test() {
+        let encoder = NonBinaryEncoder::new(15, 9, 2, &get_gf4_symbols(), &get_gf16_prime_poly());
+        assert_eq!(encoder.gen_poly().len(), 7);
+
+        let field = &encoder.symbols.field;
+        let mut x_n_plus_1 = vec![-1; 16];
+        x_n_plus_1[0] = 0;
+        x_n_plus_1[15] = 0;
+        let remainder = field.remainder_divide_polys(&x_n_plus_1, &encoder.gen_poly);
+        assert_eq!(remainder, vec![]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn validate_params_fail_with_wrong_k() {
+        NonBinaryEncoder::new(15, 11, 2, &get_gf4_symbols(), &get_gf16_prime_poly());
+    }
+
+    #[test]
+    fn binary_symbols_match_binary_encoder_test() {
+        let encoder = NonBinaryEncoder::new(15, 7, 2, &bitvec![1, 1], &get_gf16_prime_poly());
+        let binary_encoder = Encoder::new(15, 7, 2, &get_gf16_prime_poly());
+
+        let data = vec![1, 0, 1, 1, 0, 0, 1];
+        let result = encoder.encode(&data).unwrap();
+        let expected: Vec<u8> = binary_encoder
+            .encode(&data.iter().map(|bit| *bit == 1).collect())
+            .unwrap()
+            .iter()
+            .by_vals()
+            .map(|bit| bit as u8)
+            .collect();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn decode_all_double_errors_n15_k9_t2_test() {
+        let encoder = NonBinaryEncoder::new(15, 9, 2, &get_gf4_symbols(), &get_gf16_prime_poly());
+        let decoder = NonBinaryDecoder::new(15, 9, 2, &get_gf4_symbols(), &get_gf16_prime_poly());
+        let data = vec![3, 1, 0, 2, 2, 1, 3, 0, 1];
+        let encoded = encoder.encode(&data).unwrap();
+        assert_eq!(decoder.decode(&encoded).unwrap(), (data.clone(), vec![]));
+
+        for first in 0..15 {
+            for second in (first + 1)..15 {
+                let mut corrupted = encoded.clone();
+                corrupted[first] ^= 2;
+                corrupted[second] ^= 3;
+                let (decoded, corrections) = decoder.decode(&corrupted).unwrap();
+                assert_eq!(decoded, data);
+                assert_eq!(corrections, vec![(first, 2), (second, 3)]);
+            }
+        }
+    }
+
+    #[test]
+    fn encode_decode_gf8_n63_k51_t3_test() {
+        let symbol_prime_poly = bitvec![1, 0, 1, 1];
+        let prime_poly = bitvec![1, 0, 0, 0, 0, 1, 1];
+        let encoder = NonBinaryEncoder::new(63, 51, 3, &symbol_prime_poly, &prime_poly);
+        let decoder = NonBinaryDecoder::new(63, 51, 3, &symbol_prime_poly, &prime_poly);
+        let data: Vec<u8> = (0..51).map(|i| (i * 5 % 8) as u8).collect();
+
+        let mut corrupted = encoder.encode(&data).unwrap();
+        corrupted[2] ^= 7;
+        corrupted[40] ^= 1;
+        corrupted[60] ^= 4;
+        let (decoded, corrections) = decoder.decode(&corrupted).unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(corrections, vec![(2, 7), (40, 1), (60, 4)]);
+    }
+
+    #[test]
+    fn encode_decode_shortened_test() {
+        let encoder = NonBinaryEncoder::new(12, 6, 2, &get_gf4_symbols(), &get_gf16_prime_poly());
+        let decoder = NonBinaryDecoder::new(12, 6, 2, &get_gf4_symbols(), &get_gf16_prime_poly());
+        let data = vec![1, 2, 3, 3, 2, 1];
+
+        let mut corrupted = encoder.encode(&data).unwrap();
+        corrupted[0] ^= 1;
+        corrupted[11] ^= 2;
+        let (decoded, corrections) = decoder.decode(&corrupted).unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(corrections, vec![(0, 1), (11, 2)]);
+    }
+}
@@ -0,0 +1,275 @@
+use crate::berlekamp_decoder;
+use crate::forney;
+use crate::galois_field::GaloisField;
+use bitvec::prelude::*;
+
+/// Reed-Solomon code over GF(2^m), m <= 8, working on byte symbols.
+/// The generator polynomial has the roots alpha^1..alpha^(n - k) and code
+/// words are the message followed by n - k parity symbols. Codes with
+/// n < 2^m - 1 are shortened.
+#[derive(Clone)]
+pub struct RsEncoder {
+    n: i32,
+    k: i32,
+    field: GaloisField,
+    gen_poly: Vec<i32>,
+}
+
+impl RsEncoder {
+    pub fn new(n: i32, k: i32, prime_poly: &BitVec) -> RsEncoder {
+        let field = GaloisField::new(prime_poly);
+        validate_rs_params(n, k, &field);
+        RsEncoder {
+            n: n,
+            k: k,
+            gen_poly: get_rs_gen_poly(n - k, 1, &field),
+            field: field,
+        }
+    }
+
+    pub fn encode(&self, data: &[u8]) -> Result<Vec<u8>, String> {
+        if data.len() as i32 > self.k {
+            return Err("Encode: to long data!".to_owned());
+        }
+
+        let control_len = (self.n - self.k) as usize;
+        let mut data_poly = symbols_to_poly(data, &self.field)?;
+        data_poly.extend(vec![-1; control_len]);
+
+        let remainder = self
+            .field
+            .remainder_divide_polys(&data_poly, &self.gen_poly);
+        let mut encoded = data.to_vec();
+        encoded.extend(vec![0; control_len - remainder.len()]);
+        encoded.extend(poly_to_symbols(&remainder, &self.field));
+
+        Ok(encoded)
+    }
+}
+
+/// Corrected symbols as (position in the received word, error value) pairs.
+pub type Corrections = Vec<(usize, u8)>;
+
+#[derive(Clone)]
+pub struct RsDecoder {
+    n: i32,
+    k: i32,
+    first_root: i32,
+    field: GaloisField,
+}
+
+impl RsDecoder {
+    pub fn new(n: i32, k: i32, prime_poly: &BitVec) -> RsDecoder {
+        let field = GaloisField::new(prime_poly);
+        validate_rs_params(n, k, &field);
+        RsDecoder {
+            n: n,
+            k: k,
+            first_root: 1,
+            field: field,
+        }
+    }
+
+    /// Returns the decoded message and the corrected symbols.
+    pub fn decode(&self, received: &[u8]) -> Result<(Vec<u8>, Corrections), String> {
+        let control_len = (self.n - self.k) as usize;
+        if received.len() > self.n as usize || received.len() < control_len {
+            return Err("Encoded data has wrong length!".to_owned());
+        }
+        let msg_len = received.len() - control_len;
+
+        let received_poly = symbols_to_poly(received, &self.field)?;
+        let syndroms_alphas: Vec<i32> = (0..control_len as i32)
+            .map(|j| {
+                self.field
+                    .evaluate_poly(&received_poly, self.first_root + j)
+            })
+            .collect();
+        if syndroms_alphas.iter().all(|syndrome| *syndrome == -1) {
+            return Ok((received[..msg_len].to_vec(), Vec::new()));
+        }
+
+        let (sigma, lu) = berlekamp_decoder::find_error_locator(&self.field, &syndroms_alphas, 1);
+        let roots = self.field.find_roots(&sigma);
+        if lu > control_len as i32 / 2 || roots.len() != sigma.len() - 1 || roots.len() as i32 != lu
+        {
+            return Err("Too many errors. Could not decode".to_owned());
+        }
+
+        let values = forney::error_values(
+            &self.field,
+            &syndroms_alphas,
+            &sigma,
+            &roots,
+            self.first_root,
+        )?;
+
+        let mut corrected = received.to_vec();
+        let mut corrections = Vec::new();
+        for (root, value) in roots.iter().zip(values) {
+            // root is the inverse of the error locator X = alpha^position
+            let position = self.field.alpha_mod_order(-root) as usize;
+            if position >= received.len() {
+                return Err("Error located outside of the code word. Could not decode".to_owned());
+            }
+            let value = self.field.alpha_to_value(value) as u8;
+
+            let idx = received.len() - 1 - position;
+            corrected[idx] ^= value;
+            corrections.push((idx, value));
+        }
+        corrections.sort();
+
+        Ok((corrected[..msg_len].to_vec(), corrections))
+    }
+}
+
+fn validate_rs_params(n: i32, k: i32, field: &GaloisField) {
+    if field.m() > 8 || n > field.order() || k <= 0 || k >= n {
+        panic!(
+            "Bad Reed-Solomon parameters. n: {}, k: {}, m: {}",
+            n,
+            k,
+            field.m()
+        );
+    }
+}
+
+/// Product of (x + alpha^i) for i in first_root..first_root + control_len.
+fn get_rs_gen_poly(control_len: i32, first_root: i32, field: &GaloisField) -> Vec<i32> {
+    (first_root..(first_root + control_len)).fold(vec![0], |gen_poly, i| {
+        field.multiply_polys(&gen_poly, &vec![0, field.alpha_mod_order(i)])
+    })
+}
+
+fn symbols_to_poly(symbols: &[u8], field: &GaloisField) -> Result<Vec<i32>, String> {
+    symbols
+        .iter()
+        .map(|symbol| {
+            if *symbol as i32 > field.order() {
+                return Err(format!("Symbol {} is out of the field!", symbol));
+            }
+            Ok(field.value_to_alpha(*symbol as u32))
+        })
+        .collect()
+}
+
+fn poly_to_symbols(poly: &[i32], field: &GaloisField) -> Vec<u8> {
+    poly.iter()
+        .map(|alpha| field.alpha_to_value(*alpha) as u8)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_prime_poly() -> BitVec {
+        bitvec![1, 0, 0, 0, 1, 1, 1, 0, 1]
+    }
+
+    #[test]
+    fn get_rs_gen_poly_test() {
+        let field = GaloisField::new(&get_prime_poly());
+        let gen_poly = get_rs_gen_poly(4, 1, &field);
+        assert_eq!(
+            poly_to_symbols(&gen_poly, &field),
+            vec![1, 30, 216, 231, 116]
+        );
+    }
+
+    #[test]
+    fn encode_test() {
+        let encoder = RsEncoder::new(15, 11, &bitvec![1, 0, 0, 1, 1]);
+        let data: Vec<u8> = (1..12).collect();
+        let result = encoder.encode(&data).unwrap();
+        let expected = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 11, 10, 14, 6];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn encode_symbol_out_of_field_test() {
+        let encoder = RsEncoder::new(15, 11, &bitvec![1, 0, 0, 1, 1]);
+        assert!(encoder.encode(&[1, 16]).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn validate_params_fail_when_n_too_long() {
+        RsEncoder::new(16, 11, &bitvec![1, 0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn decode_no_error_test() {
+        let decoder = RsDecoder::new(15, 11, &bitvec![1, 0, 0, 1, 1]);
+        let received = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 11, 10, 14, 6];
+        let (decoded, corrections) = decoder.decode(&received).unwrap();
+        assert_eq!(decoded, (1..12).collect::<Vec<u8>>());
+        assert!(corrections.is_empty());
+    }
+
+    #[test]
+    fn decode_corrects_two_symbols_test() {
+        let decoder = RsDecoder::new(15, 11, &bitvec![1, 0, 0, 1, 1]);
+        let received = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 11, 10, 14, 6];
+        for first in 0..15 {
+            for second in (first + 1)..15 {
+                let mut corrupted = received.clone();
+                corrupted[first] ^= 9;
+                corrupted[second] ^= 1;
+                let (decoded, corrections) = decoder.decode(&corrupted).unwrap();
+                assert_eq!(decoded, (1..12).collect::<Vec<u8>>());
+                assert_eq!(corrections, vec![(first, 9), (second, 1)]);
+            }
+        }
+    }
+
+    #[test]
+    fn encode_decode_rs_255_223_test() {
+        let encoder = RsEncoder::new(255, 223, &get_prime_poly());
+        let decoder = RsDecoder::new(255, 223, &get_prime_poly());
+        let data: Vec<u8> = (0..223).map(|i| (i * 7 + 3) as u8).collect();
+        let encoded = encoder.encode(&data).unwrap();
+        assert_eq!(encoded.len(), 255);
+
+        let mut corrupted = encoded.clone();
+        let mut expected_corrections = Vec::new();
+        for i in 0..16 {
+            let position = i * 15 + 4;
+            corrupted[position] ^= (i + 1) as u8;
+            expected_corrections.push((position, (i + 1) as u8));
+        }
+        let (decoded, corrections) = decoder.decode(&corrupted).unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(corrections, expected_corrections);
+    }
+
+    #[test]
+    fn encode_decode_shortened_test() {
+        let encoder = RsEncoder::new(40, 30, &get_prime_poly());
+        let decoder = RsDecoder::new(40, 30, &get_prime_poly());
+        let data: Vec<u8> = (0..30).map(|i| (255 - i * 5) as u8).collect();
+        let mut encoded = encoder.encode(&data).unwrap();
+        encoded[0] ^= 0xff;
+        encoded[21] ^= 0x10;
+        encoded[39] ^= 0x01;
+
+        let (decoded, corrections) = decoder.decode(&encoded).unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(corrections, vec![(0, 0xff), (21, 0x10), (39, 0x01)]);
+    }
+
+    #[test]
+    fn decode_too_many_errors_test() {
+        let decoder = RsDecoder::new(15, 11, &bitvec![1, 0, 0, 1, 1]);
+        let mut received = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 11, 10, 14, 6];
+        // three errors leave no code word within distance t = 2
+        received[0] ^= 1;
+        received[5] ^= 2;
+        received[10] ^= 3;
+        assert_eq!(
+            decoder.decode(&received),
+            Err("Too many errors. Could not decode".to_owned())
+        );
+    }
+}
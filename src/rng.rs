@@ -0,0 +1,59 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::env;
+
+/// Environment variable fixing the seed of randomized runs and tests.
+pub const SEED_VARIABLE: &str = "BCH_SEED";
+
+/// Seed from BCH_SEED if set, a fresh random one otherwise.
+pub fn seed_from_env() -> Result<u64, String> {
+    match env::var(SEED_VARIABLE) {
+        Ok(seed) => parse_seed(&seed),
+        Err(_) => Ok(rand::random()),
+    }
+}
+
+fn parse_seed(seed: &str) -> Result<u64, String> {
+    seed.parse()
+        .map_err(|_| format!("{} has to be an unsigned integer: {}", SEED_VARIABLE, seed))
+}
+
+pub fn seeded_rng(seed: u64) -> StdRng {
+    StdRng::seed_from_u64(seed)
+}
+
+/// RNG for tests. The seed is printed, the test harness shows it when the
+/// test fails, and `BCH_SEED=<seed> cargo test <name>` replays the run.
+#[cfg(test)]
+pub fn test_rng() -> StdRng {
+    let seed = seed_from_env().unwrap();
+    println!("{}={}", SEED_VARIABLE, seed);
+    seeded_rng(seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn parse_seed_test() {
+        assert_eq!(parse_seed("42"), Ok(42));
+        assert!(parse_seed("-1").is_err());
+        assert!(parse_seed("0x10").is_err());
+    }
+
+    #[test]
+    fn seeded_rng_is_deterministic_test() {
+        let first: Vec<u32> = seeded_rng(11)
+            .sample_iter(&rand::distributions::Standard)
+            .take(5)
+            .collect();
+        let second: Vec<u32> = seeded_rng(11)
+            .sample_iter(&rand::distributions::Standard)
+            .take(5)
+            .collect();
+        assert_eq!(first, second);
+        assert_ne!(seeded_rng(12).gen::<u64>(), seeded_rng(11).gen::<u64>());
+    }
+}
@@ -1,25 +1,31 @@
 use crate::bch_bitvec::*;
+use crate::bch_code::BchCode;
 use crate::common;
 use crate::decoder::*;
-use bitvec::*;
+use bitvec::prelude::*;
 
 #[derive(Clone)]
 pub struct SimpleDecoder {
     n: i32,
     k: i32,
     t: i32,
+    cycle_len: i32, // length of the cyclic code, greater than n for shortened codes
     gen_poly: BitVec,
 }
 
 impl SimpleDecoder {
     pub fn new(n: i32, k: i32, t: i32, prime_poly: &BitVec) -> SimpleDecoder {
-        let prime_degree = prime_poly.len() as i32 - 1;
-        let gen_poly = common::get_gen_poly(prime_degree, t, prime_poly);
-        common::validate_params(n, k, &gen_poly, prime_poly);
+        SimpleDecoder::from_code(&BchCode::new(n, k, t, prime_poly))
+    }
+
+    pub fn from_code(code: &BchCode) -> SimpleDecoder {
+        let gen_poly = code.gen_poly();
+        common::validate_params(code.n, code.k, &gen_poly, &code.prime_poly);
         SimpleDecoder {
-            n: n,
-            k: k,
-            t: t,
+            n: code.n,
+            k: code.k,
+            t: code.t,
+            cycle_len: code.cycle_len,
             gen_poly: gen_poly,
         }
     }
@@ -29,6 +35,7 @@ impl SimpleDecoder {
             n: n,
             k: k,
             t: t,
+            cycle_len: n,
             gen_poly: gen_poly,
         }
     }
@@ -39,10 +46,17 @@ impl Decoder for SimpleDecoder {
         if encoded.len() > self.n as usize {
             return Err("Encoded data is too long!".to_owned());
         }
+        if encoded.len() < (self.n - self.k) as usize {
+            return Err("Encoded data is too short!".to_owned());
+        }
 
+        // shortened words are decoded as full cyclic words with leading zeros
+        let padding = self.cycle_len as usize - encoded.len();
+        let msg_len = encoded.len() - (self.n - self.k) as usize;
         let mut encoded_clone = encoded.clone();
+        encoded_clone.precede_with_zeros(padding);
 
-        for i in 0..self.n {
+        for i in 0..self.cycle_len {
             let syndrome = encoded_clone.remainder_divide(&self.gen_poly)?;
 
             let hamming_weight = syndrome.count_ones();
@@ -53,7 +67,12 @@ impl Decoder for SimpleDecoder {
                 let mut corrected = encoded_clone ^ extended_syndrome;
 
                 corrected.shift_cyclic(-i);
-                let decoded = corrected.iter().take(self.k as usize).collect();
+                if corrected.iter().by_vals().take(padding).any(|bit| bit) {
+                    return Err(
+                        "Error located outside of the code word. Could not decode".to_owned()
+                    );
+                }
+                let decoded = corrected.iter().skip(padding).take(msg_len).collect();
 
                 let mut error = syndrome;
                 error.shift_cyclic(-i);
@@ -0,0 +1,415 @@
+use crate::channel::Channel;
+use crate::common;
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Stopping rule and seed of a Monte Carlo run. A point stops after
+/// max_frames frames or once target_frame_errors frame errors were seen.
+#[derive(Clone, Debug)]
+pub struct SimulationConfig {
+    pub max_frames: u64,
+    pub target_frame_errors: u64,
+    pub seed: u64,
+}
+
+impl SimulationConfig {
+    pub fn new(max_frames: u64, target_frame_errors: u64, seed: u64) -> SimulationConfig {
+        SimulationConfig {
+            max_frames: max_frames,
+            target_frame_errors: target_frame_errors,
+            seed: seed,
+        }
+    }
+
+    /// Every point of a curve gets its own seed so it can be rerun alone.
+    pub fn point_seed(&self, point: usize) -> u64 {
+        self.seed.wrapping_add(point as u64)
+    }
+}
+
+/// Counts gathered at one channel parameter. Decoder failures are frames
+/// the decoder rejected, their message is taken from the received word.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationResult {
+    pub parameter: f64,
+    pub frames: u64,
+    pub frame_errors: u64,
+    pub decoder_failures: u64,
+    pub bits: u64,
+    pub bit_errors: u64,
+}
+
+impl SimulationResult {
+    pub fn fer(&self) -> f64 {
+        ratio(self.frame_errors, self.frames)
+    }
+
+    pub fn ber(&self) -> f64 {
+        ratio(self.bit_errors, self.bits)
+    }
+
+    /// 95% Wilson score interval of the frame error rate.
+    pub fn fer_interval(&self) -> (f64, f64) {
+        wilson_interval(self.frame_errors, self.frames, 1.96)
+    }
+
+    /// 95% Wilson score interval of the bit error rate. Errors within a
+    /// frame are not independent, so the interval is optimistic.
+    pub fn ber_interval(&self) -> (f64, f64) {
+        wilson_interval(self.bit_errors, self.bits, 1.96)
+    }
+}
+
+/// Sends random k-bit messages through the encoder, channel and decoder.
+pub fn simulate_point(
+    encoder: &Encoder,
+    decoder: &dyn Decoder,
+    k: usize,
+    channel: &mut dyn Channel,
+    parameter: f64,
+    config: &SimulationConfig,
+    rng: &mut StdRng,
+) -> SimulationResult {
+    let mut result = SimulationResult {
+        parameter: parameter,
+        frames: 0,
+        frame_errors: 0,
+        decoder_failures: 0,
+        bits: 0,
+        bit_errors: 0,
+    };
+
+    while result.frames < config.max_frames && result.frame_errors < config.target_frame_errors {
+        let msg = common::get_random_msg(k, rng);
+        let encoded = encoder.encode(&msg).unwrap();
+        let received = channel.transmit(&encoded, rng);
+        let decoded = match decoder.decode(&received) {
+            Ok((decoded, _)) => decoded,
+            Err(_) => {
+                result.decoder_failures += 1;
+                received.iter().take(k).collect()
+            }
+        };
+
+        let bit_errors = msg
+            .iter()
+            .zip(decoded.iter())
+            .filter(|(sent, got)| sent != got)
+            .count() as u64;
+        result.frames += 1;
+        result.bits += k as u64;
+        result.bit_errors += bit_errors;
+        if bit_errors != 0 {
+            result.frame_errors += 1;
+        }
+    }
+    result
+}
+
+/// Simulates every channel parameter with a channel built by make_channel.
+pub fn simulate_curve<F>(
+    encoder: &Encoder,
+    decoder: &dyn Decoder,
+    k: usize,
+    parameters: &[f64],
+    make_channel: F,
+    config: &SimulationConfig,
+) -> Vec<SimulationResult>
+where
+    F: Fn(f64) -> Box<dyn Channel>,
+{
+    parameters
+        .iter()
+        .enumerate()
+        .map(|(point, parameter)| {
+            let mut rng = StdRng::seed_from_u64(config.point_seed(point));
+            let mut channel = make_channel(*parameter);
+            simulate_point(
+                encoder,
+                decoder,
+                k,
+                channel.as_mut(),
+                *parameter,
+                config,
+                &mut rng,
+            )
+        })
+        .collect()
+}
+
+/// Same as simulate_curve with the points spread over worker threads. Every
+/// point keeps its seed, the results do not depend on the number of threads.
+/// With a single thread the points are simulated by simulate_curve.
+pub fn simulate_curve_parallel<F>(
+    encoder: &Encoder,
+    decoder: &(dyn Decoder + Sync),
+    k: usize,
+    parameters: &[f64],
+    make_channel: F,
+    config: &SimulationConfig,
+    threads: usize,
+) -> Vec<SimulationResult>
+where
+    F: Fn(f64) -> Box<dyn Channel> + Sync,
+{
+    if threads <= 1 {
+        return simulate_curve(encoder, decoder, k, parameters, make_channel, config);
+    }
+    let next_point = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..threads.min(parameters.len()) {
+            let sender = sender.clone();
+            let next_point = &next_point;
+            let make_channel = &make_channel;
+            scope.spawn(move || loop {
+                let point = next_point.fetch_add(1, Ordering::SeqCst);
+                if point >= parameters.len() {
+                    break;
+                }
+                let mut rng = StdRng::seed_from_u64(config.point_seed(point));
+                let mut channel = make_channel(parameters[point]);
+                let result = simulate_point(
+                    encoder,
+                    decoder,
+                    k,
+                    channel.as_mut(),
+                    parameters[point],
+                    config,
+                    &mut rng,
+                );
+                sender.send((point, result)).unwrap();
+            });
+        }
+    });
+    drop(sender);
+
+    let mut results: Vec<(usize, SimulationResult)> = receiver.iter().collect();
+    results.sort_by_key(|(point, _)| *point);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+pub fn to_csv(results: &[SimulationResult]) -> String {
+    let mut csv = "parameter,frames,frame_errors,decoder_failures,fer,fer_low,fer_high,\
+                   bits,bit_errors,ber,ber_low,ber_high\n"
+        .to_owned();
+    for result in results {
+        let (fer_low, fer_high) = result.fer_interval();
+        let (ber_low, ber_high) = result.ber_interval();
+        csv += &format!(
+            "{},{},{},{},{:e},{:e},{:e},{},{},{:e},{:e},{:e}\n",
+            result.parameter,
+            result.frames,
+            result.frame_errors,
+            result.decoder_failures,
+            result.fer(),
+            fer_low,
+            fer_high,
+            result.bits,
+            result.bit_errors,
+            result.ber(),
+            ber_low,
+            ber_high
+        );
+    }
+    csv
+}
+
+pub fn to_json(results: &[SimulationResult]) -> String {
+    let points: Vec<String> = results
+        .iter()
+        .map(|result| {
+            let (fer_low, fer_high) = result.fer_interval();
+            let (ber_low, ber_high) = result.ber_interval();
+            format!(
+                "  {{\"parameter\": {}, \"frames\": {}, \"frame_errors\": {}, \
+                 \"decoder_failures\": {}, \"fer\": {:e}, \"fer_low\": {:e}, \
+                 \"fer_high\": {:e}, \"bits\": {}, \"bit_errors\": {}, \"ber\": {:e}, \
+                 \"ber_low\": {:e}, \"ber_high\": {:e}}}",
+                result.parameter,
+                result.frames,
+                result.frame_errors,
+                result.decoder_failures,
+                result.fer(),
+                fer_low,
+                fer_high,
+                result.bits,
+                result.bit_errors,
+                result.ber(),
+                ber_low,
+                ber_high
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", points.join(",\n"))
+}
+
+fn ratio(errors: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    errors as f64 / total as f64
+}
+
+fn wilson_interval(errors: u64, total: u64, z: f64) -> (f64, f64) {
+    if total == 0 {
+        return (0.0, 1.0);
+    }
+    let n = total as f64;
+    let p = errors as f64 / n;
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half_width = z / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    (
+        (center - half_width).max(0.0),
+        (center + half_width).min(1.0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::berlekamp_decoder::BerlekampDecoder;
+    use crate::channel::{BinarySymmetricChannel, FixedErrorsChannel};
+    use crate::simple_decoder::SimpleDecoder;
+    use bitvec::prelude::*;
+
+    fn get_prime_poly() -> BitVec {
+        bitvec![1, 0, 0, 1, 0, 1]
+    }
+
+    #[test]
+    fn correctable_errors_give_no_frame_errors_test() {
+        let encoder = Encoder::new(31, 16, 3, &get_prime_poly());
+        let decoder = BerlekampDecoder::new(31, 16, 3, &get_prime_poly());
+        let config = SimulationConfig::new(200, 10, 1);
+        let results = simulate_curve(
+            &encoder,
+            &decoder,
+            16,
+            &[0.0, 3.0],
+            |errors| Box::new(FixedErrorsChannel::new(errors as usize)),
+            &config,
+        );
+
+        for result in results {
+            assert_eq!(result.frames, 200);
+            assert_eq!(result.frame_errors, 0);
+            assert_eq!(result.bits, 200 * 16);
+            assert_eq!(result.fer(), 0.0);
+        }
+    }
+
+    #[test]
+    fn stops_at_target_frame_errors_test() {
+        let encoder = Encoder::new(15, 7, 2, &bitvec![1, 0, 0, 1, 1]);
+        let decoder = SimpleDecoder::new(15, 7, 2, &bitvec![1, 0, 0, 1, 1]);
+        let config = SimulationConfig::new(100000, 20, 2);
+        let results = simulate_curve(
+            &encoder,
+            &decoder,
+            7,
+            &[0.3],
+            |p| Box::new(BinarySymmetricChannel::new(p)),
+            &config,
+        );
+
+        assert_eq!(results[0].frame_errors, 20);
+        assert!(results[0].frames < 100000);
+        assert!(results[0].bit_errors >= 20);
+    }
+
+    #[test]
+    fn same_seed_same_results_test() {
+        let encoder = Encoder::new(31, 16, 3, &get_prime_poly());
+        let decoder = BerlekampDecoder::new(31, 16, 3, &get_prime_poly());
+        let config = SimulationConfig::new(300, 1000, 42);
+        let run = || {
+            simulate_curve(
+                &encoder,
+                &decoder,
+                16,
+                &[0.02, 0.05],
+                |p| Box::new(BinarySymmetricChannel::new(p)),
+                &config,
+            )
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn parallel_matches_sequential_test() {
+        let encoder = Encoder::new(31, 16, 3, &get_prime_poly());
+        let decoder = BerlekampDecoder::new(31, 16, 3, &get_prime_poly());
+        let config = SimulationConfig::new(300, 1000, 42);
+        let parameters = [0.01, 0.03, 0.05, 0.07, 0.09];
+        let make_channel = |p| Box::new(BinarySymmetricChannel::new(p)) as Box<dyn Channel>;
+        let sequential = simulate_curve(&encoder, &decoder, 16, &parameters, make_channel, &config);
+        for threads in 1..4 {
+            let parallel = simulate_curve_parallel(
+                &encoder,
+                &decoder,
+                16,
+                &parameters,
+                make_channel,
+                &config,
+                threads,
+            );
+            assert_eq!(parallel, sequential);
+        }
+    }
+
+    #[test]
+    fn wilson_interval_test() {
+        let (low, high) = wilson_interval(0, 100, 1.96);
+        assert_eq!(low, 0.0);
+        assert!((high - 0.03699).abs() < 1e-4);
+
+        let (low, high) = wilson_interval(50, 100, 1.96);
+        assert!((low - 0.4038).abs() < 1e-4);
+        assert!((high - 0.5962).abs() < 1e-4);
+        assert_eq!(wilson_interval(0, 0, 1.96), (0.0, 1.0));
+    }
+
+    #[test]
+    fn to_csv_test() {
+        let result = SimulationResult {
+            parameter: 0.01,
+            frames: 100,
+            frame_errors: 50,
+            decoder_failures: 10,
+            bits: 1000,
+            bit_errors: 0,
+        };
+        let csv = to_csv(&[result]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("parameter,frames,frame_errors,decoder_failures,fer"));
+        assert!(lines[1].starts_with("0.01,100,50,10,5e-1,"));
+        assert!(lines[1].contains(",1000,0,0e0,0e0,"));
+    }
+
+    #[test]
+    fn to_json_test() {
+        let result = SimulationResult {
+            parameter: 0.01,
+            frames: 100,
+            frame_errors: 50,
+            decoder_failures: 10,
+            bits: 1000,
+            bit_errors: 0,
+        };
+        let json = to_json(&[result.clone(), result]);
+        assert!(
+            json.starts_with("[\n  {\"parameter\": 0.01, \"frames\": 100, \"frame_errors\": 50, ")
+        );
+        assert!(json.contains("\"ber\": 0e0, "));
+        assert!(json.ends_with("}\n]\n"));
+        assert_eq!(json.matches("},\n").count(), 1);
+    }
+}
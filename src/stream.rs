@@ -0,0 +1,386 @@
+use crate::bch_code::BchCode;
+use crate::berlekamp_decoder::BerlekampDecoder;
+use crate::container::{Header, HEADER_LEN};
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::framing::{self, DecodeStats};
+use bitvec::prelude::*;
+use std::collections::VecDeque;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+fn to_io_error(err: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// Encodes a payload into a container on the fly. At most one block of
+/// message bits and one byte of code word bits are held.
+pub struct BchWriter<W: Write> {
+    inner: W,
+    code: BchCode,
+    encoder: Encoder,
+    payload_len: Option<u64>,
+    written: u64,
+    msg: BitVec,
+    pending: BitVec,
+}
+
+impl<W: Write> BchWriter<W> {
+    /// Writes the container header, the payload has to have payload_len bytes.
+    pub fn new(inner: W, code: &BchCode, payload_len: u64) -> Result<BchWriter<W>, String> {
+        let header = Header::new(code, payload_len)?;
+        BchWriter::with_header(inner, code, &header, Some(payload_len))
+    }
+
+    fn with_header(
+        mut inner: W,
+        code: &BchCode,
+        header: &Header,
+        payload_len: Option<u64>,
+    ) -> Result<BchWriter<W>, String> {
+        inner
+            .write_all(&header.to_bytes())
+            .map_err(|err| format!("Cannot write container header: {}", err))?;
+        Ok(BchWriter {
+            inner: inner,
+            code: code.clone(),
+            encoder: Encoder::from_code(code),
+            payload_len: payload_len,
+            written: 0,
+            msg: BitVec::new(),
+            pending: BitVec::new(),
+        })
+    }
+
+    /// Encodes the zero padded final block and writes the last bits.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.payload_len.is_none() {
+            return Err(to_io_error(
+                "Payload of unknown length, finish with finish_seek".to_owned(),
+            ));
+        }
+        self.check_written()?;
+        self.write_last_block()?;
+        Ok(self.inner)
+    }
+
+    fn check_written(&self) -> io::Result<()> {
+        match self.payload_len {
+            Some(payload_len) if payload_len != self.written => Err(to_io_error(format!(
+                "Expected {} payload bytes, got {}",
+                payload_len, self.written
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn write_last_block(&mut self) -> io::Result<()> {
+        if !self.msg.is_empty() {
+            let padding = self.code.k as usize - self.msg.len();
+            self.msg.extend(bitvec![0; padding]);
+            self.encode_block()?;
+        }
+        let last = framing::bits_to_bytes(&self.pending);
+        self.pending = BitVec::new();
+        self.inner.write_all(&last)?;
+        self.inner.flush()
+    }
+
+    fn encode_block(&mut self) -> io::Result<()> {
+        let word = self.encoder.encode(&self.msg).map_err(to_io_error)?;
+        self.msg = BitVec::new();
+        self.pending.extend(word.iter());
+        let whole = self.pending.len() / 8 * 8;
+        let bytes: BitVec = self.pending.iter().take(whole).collect();
+        self.pending = self.pending.iter().skip(whole).collect();
+        self.inner.write_all(&framing::bits_to_bytes(&bytes))
+    }
+}
+
+impl<W: Write + Seek> BchWriter<W> {
+    /// Writes a header without a payload length, for a payload of unknown
+    /// length such as a pipe. finish_seek rewrites the header once the
+    /// length is known, so inner has to be seekable.
+    pub fn with_unknown_len(inner: W, code: &BchCode) -> Result<BchWriter<W>, String> {
+        BchWriter::with_header(inner, code, &Header::new(code, 0)?, None)
+    }
+
+    /// Like finish, but writes the header again with the number of payload
+    /// bytes written. Works for a declared length too.
+    pub fn finish_seek(mut self) -> io::Result<W> {
+        self.check_written()?;
+        self.write_last_block()?;
+        let header = Header::new(&self.code, self.written).map_err(to_io_error)?;
+        let end = self.inner.stream_position()?;
+        self.inner.seek(SeekFrom::Start(0))?;
+        self.inner.write_all(&header.to_bytes())?;
+        self.inner.seek(SeekFrom::Start(end))?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for BchWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(payload_len) = self.payload_len {
+            if self.written + buf.len() as u64 > payload_len {
+                return Err(to_io_error(format!(
+                    "Payload longer than the declared {} bytes",
+                    payload_len
+                )));
+            }
+        }
+        for byte in buf {
+            for i in (0..8).rev() {
+                self.msg.push(byte & (1 << i) != 0);
+                if self.msg.len() == self.code.k as usize {
+                    self.encode_block()?;
+                }
+            }
+        }
+        self.written += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    /// Flushes whole bytes, the bits of an unfinished block wait for finish.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decodes a container on the fly, yielding the payload without the
+/// padding of the final block.
+pub struct BchReader<R: Read> {
+    inner: R,
+    header: Header,
+    decoder: Box<dyn Decoder>,
+    encoder: Encoder,
+    received: BitVec,
+    decoded: BitVec,
+    out: VecDeque<u8>,
+    block: usize,
+    remaining: u64,
+    stats: DecodeStats,
+}
+
+impl<R: Read> BchReader<R> {
+    /// Reads the header, blocks are decoded with the Berlekamp decoder.
+    pub fn new(mut inner: R) -> Result<BchReader<R>, String> {
+        let mut bytes = [0u8; HEADER_LEN];
+        inner
+            .read_exact(&mut bytes)
+            .map_err(|err| format!("Cannot read container header: {}", err))?;
+        let header = Header::from_bytes(&bytes)?;
+        Ok(BchReader {
+            inner: inner,
+            decoder: Box::new(BerlekampDecoder::from_code(&header.code)),
+            encoder: Encoder::from_code(&header.code),
+            received: BitVec::new(),
+            decoded: BitVec::new(),
+            out: VecDeque::new(),
+            block: 0,
+            remaining: header.payload_len,
+            header: header,
+            stats: DecodeStats::default(),
+        })
+    }
+
+    /// Same reader decoding with the given decoder of header().code.
+    pub fn with_decoder(mut self, decoder: Box<dyn Decoder>) -> BchReader<R> {
+        self.decoder = decoder;
+        self
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Counts of the blocks decoded so far.
+    pub fn stats(&self) -> &DecodeStats {
+        &self.stats
+    }
+
+    fn decode_next_block(&mut self) -> io::Result<()> {
+        let n = self.header.code.n as usize;
+        let missing = n.saturating_sub(self.received.len());
+        let mut bytes = vec![0u8; missing.div_ceil(8)];
+        self.inner.read_exact(&mut bytes)?;
+        self.received.extend(framing::bytes_to_bits(&bytes).iter());
+        let word: BitVec = self.received.iter().take(n).collect();
+        self.received = self.received.iter().skip(n).collect();
+
+        let (msg, block_stats) = framing::decode_block(
+            self.decoder.as_ref(),
+            &self.encoder,
+            self.header.code.k,
+            self.block,
+            &word,
+        )
+        .map_err(to_io_error)?;
+        self.block += 1;
+        self.stats.add(&block_stats);
+
+        self.decoded.extend(msg.iter());
+        let whole = ((self.decoded.len() / 8) as u64).min(self.remaining) as usize;
+        let bytes: BitVec = self.decoded.iter().take(whole * 8).collect();
+        self.decoded = self.decoded.iter().skip(whole * 8).collect();
+        self.out.extend(framing::bits_to_bytes(&bytes));
+        self.remaining -= whole as u64;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for BchReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.out.is_empty() && self.remaining > 0 {
+            self.decode_next_block()?;
+        }
+        let count = buf.len().min(self.out.len());
+        for (place, byte) in buf.iter_mut().zip(self.out.drain(..count)) {
+            *place = byte;
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::container::ContainerWriter;
+    use crate::simple_decoder::SimpleDecoder;
+    use std::io::Cursor;
+
+    fn get_code() -> BchCode {
+        BchCode::new(31, 16, 3, &bitvec![1, 0, 0, 1, 0, 1])
+    }
+
+    fn stream_encode(code: &BchCode, payload: &[u8], chunk: usize) -> Vec<u8> {
+        let mut writer = BchWriter::new(Vec::new(), code, payload.len() as u64).unwrap();
+        for part in payload.chunks(chunk) {
+            writer.write_all(part).unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn writer_matches_container_writer_test() {
+        let code = get_code();
+        for len in &[0, 1, 2, 3, 17, 100] {
+            let payload: Vec<u8> = (0..*len).map(|i| (i * 31 + 7) as u8).collect();
+            // the zero padded payload split into messages and encoded
+            let mut bits = framing::bytes_to_bits(&payload);
+            let blocks = framing::block_count(code.k, payload.len() as u64);
+            bits.extend(bitvec![0; blocks * code.k as usize - bits.len()]);
+            let encoder = Encoder::from_code(&code);
+            let words: Vec<BitVec> = (0..blocks)
+                .map(|block| {
+                    let msg: BitVec = bits
+                        .iter()
+                        .skip(block * code.k as usize)
+                        .take(code.k as usize)
+                        .collect();
+                    encoder.encode(&msg).unwrap()
+                })
+                .collect();
+            let mut container = ContainerWriter::new(Vec::new(), &code).unwrap();
+            container.write_words(payload.len() as u64, &words).unwrap();
+            let expected = container.into_inner();
+            for chunk in &[1, 5, 64] {
+                assert_eq!(
+                    stream_encode(&code, &payload, *chunk),
+                    expected,
+                    "len: {}, chunk: {}",
+                    len,
+                    chunk
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn writer_checks_declared_length_test() {
+        let mut writer = BchWriter::new(Vec::new(), &get_code(), 3).unwrap();
+        writer.write_all(&[1, 2]).unwrap();
+        assert!(writer.write_all(&[3, 4]).is_err());
+        assert!(writer.finish().is_err());
+    }
+
+    #[test]
+    fn writer_unknown_len_patches_header_test() {
+        let code = get_code();
+        let payload: Vec<u8> = (0..23).map(|i| (i * 13 + 1) as u8).collect();
+        let mut writer = BchWriter::with_unknown_len(Cursor::new(Vec::new()), &code).unwrap();
+        for part in payload.chunks(4) {
+            writer.write_all(part).unwrap();
+        }
+        let encoded = writer.finish_seek().unwrap().into_inner();
+        assert_eq!(encoded, stream_encode(&code, &payload, 23));
+
+        let mut decoded = Vec::new();
+        BchReader::new(&encoded[..])
+            .unwrap()
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, payload);
+    }
+
+    #[test]
+    fn writer_unknown_len_needs_seek_test() {
+        let writer = BchWriter::with_unknown_len(Cursor::new(Vec::new()), &get_code()).unwrap();
+        assert!(writer.finish().is_err());
+    }
+
+    #[test]
+    fn reader_round_trip_test() {
+        let code = BchCode::new(63, 0, 4, &bitvec![1, 0, 0, 0, 0, 1, 1]).with_derived_k();
+        let payload: Vec<u8> = (0..1000).map(|i| (i % 251) as u8).collect();
+        let mut encoded = stream_encode(&code, &payload, 100);
+
+        let header = Header::new(&code, 1000).unwrap();
+        let mut flips = 0;
+        for block in (0..header.block_count()).step_by(3) {
+            for bit in &[0, 20, 62] {
+                let offset = header.bit_offset(block, *bit);
+                encoded[offset / 8] ^= 0x80 >> (offset % 8);
+                flips += 1;
+            }
+        }
+
+        let mut reader = BchReader::new(&encoded[..]).unwrap();
+        let mut decoded: Vec<u8> = Vec::new();
+        let mut buf = [0u8; 7];
+        loop {
+            let count = reader.read(&mut buf).unwrap();
+            if count == 0 {
+                break;
+            }
+            decoded.extend(&buf[..count]);
+        }
+        assert_eq!(decoded, payload);
+        assert_eq!(reader.stats().blocks, header.block_count());
+        assert_eq!(reader.stats().corrected_bits, flips);
+        assert!(reader.stats().uncorrectable_blocks.is_empty());
+    }
+
+    #[test]
+    fn reader_reports_uncorrectable_blocks_test() {
+        let code = get_code();
+        let payload = vec![0x5a; 20];
+        let mut encoded = stream_encode(&code, &payload, 20);
+        // seven errors in block 1 are beyond t
+        encoded[HEADER_LEN + 4] ^= 0b0111_1111;
+
+        let decoder = Box::new(SimpleDecoder::from_code(&code));
+        let mut reader = BchReader::new(&encoded[..]).unwrap().with_decoder(decoder);
+        let mut decoded = Vec::new();
+        reader.read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded.len(), 20);
+        assert_eq!(reader.stats().uncorrectable_blocks, vec![1]);
+    }
+
+    #[test]
+    fn reader_fails_on_truncated_stream_test() {
+        let encoded = stream_encode(&get_code(), &[1, 2, 3, 4, 5], 5);
+        let mut reader = BchReader::new(&encoded[..encoded.len() - 2]).unwrap();
+        assert!(reader.read_to_end(&mut Vec::new()).is_err());
+    }
+}
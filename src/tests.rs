@@ -1,12 +1,16 @@
 #[cfg(test)]
 mod tests {
+    use crate::bch_code::BchCode;
     use crate::berlekamp_decoder::BerlekampDecoder;
     use crate::decoder::Decoder;
     use crate::encoder::Encoder;
+    use crate::matrices::{field_parity_check_matrix, generator_matrix, parity_check_matrix};
     use crate::bch_bitvec::*;
     use crate::simple_decoder::SimpleDecoder;
     use crate::common::get_random_places;
-    use bitvec::*;
+    use crate::rng::test_rng;
+    use bitvec::prelude::*;
+    use itertools::Itertools;
 
     #[test]
     fn encode_decode_n7_k4_t1_test() {
@@ -129,6 +133,229 @@ mod tests {
         assert_eq!(decoded, msg);
     }
 
+    #[test]
+    fn encode_decode_shortened_n25_k10_t3_test() {
+        let n = 25;
+        let k = 10;
+        let t = 3;
+
+        let msg = bitvec![1, 1, 0, 1, 0, 0, 1, 0, 1, 1];
+        let prime_poly = bitvec![1, 0, 0, 1, 0, 1];
+
+        let encoder = Encoder::new(n, k, t, &prime_poly);
+        let encoded = encoder.encode(&msg).unwrap();
+        assert_eq!(encoded.len(), 25);
+
+        let mut with_burst = encoded.clone();
+        for i in 20..23 {
+            with_burst.inverse_nth(i);
+        }
+        let decoder = SimpleDecoder::new(n, k, t, &prime_poly);
+        let (decoded, _) = decoder.decode(&with_burst).unwrap();
+        assert_eq!(decoded, msg);
+
+        let decoder = BerlekampDecoder::new(n, k, t, &prime_poly);
+        for places in vec![vec![0, 12, 24], vec![3, 4], vec![24], vec![]] {
+            let mut received = encoded.clone();
+            places.iter().for_each(|place| received.inverse_nth(*place));
+            let (decoded, _) = decoder.decode(&received).unwrap();
+            assert_eq!(decoded, msg);
+        }
+    }
+
+    #[test]
+    fn encode_decode_shortened_n200_k132_t9_test() {
+        let n = 200;
+        let k = 132;
+        let t = 9;
+
+        let mut msg = bitvec![1, 0, 1, 1, 0, 0, 1];
+        msg.extend(bitvec![1; 125]);
+
+        let prime_poly = bitvec![1, 0, 1, 1, 0, 0, 0, 1, 1];
+
+        let encoder = Encoder::new(n, k, t, &prime_poly);
+        let encoded = encoder.encode(&msg).unwrap();
+
+        let decoder = BerlekampDecoder::new(n, k, t, &prime_poly);
+        let mut with_burst = encoded.clone();
+        for i in 150..159 {
+            with_burst.inverse_nth(i);
+        }
+        let (decoded, _) = decoder.decode(&with_burst).unwrap();
+        assert_eq!(decoded, msg);
+
+        let mut received = encoded.clone();
+        for place in vec![0, 13, 57, 99, 131, 150, 170, 188, 199] {
+            received.inverse_nth(place);
+        }
+        let (decoded, _) = decoder.decode(&received).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn encode_decode_non_primitive_n21_k12_t2_test() {
+        let msg = bitvec![1, 0, 1, 1, 0, 0, 1, 1, 1, 0, 0, 1];
+        let code = BchCode::new_non_primitive(21, 12, 2, &bitvec![1, 0, 0, 0, 0, 1, 1]);
+
+        let encoder = Encoder::from_code(&code);
+        let encoded = encoder.encode(&msg).unwrap();
+        assert_eq!(encoded.len(), 21);
+
+        let mut with_burst = encoded.clone();
+        with_burst.inverse_nth(20);
+        with_burst.inverse_nth(0);
+        let decoder = SimpleDecoder::from_code(&code);
+        let (decoded, _) = decoder.decode(&with_burst).unwrap();
+        assert_eq!(decoded, msg);
+
+        let decoder = BerlekampDecoder::from_code(&code);
+        for places in (0..21).combinations(2) {
+            let mut received = encoded.clone();
+            places.iter().for_each(|place| received.inverse_nth(*place));
+            let (decoded, _) = decoder.decode(&received).unwrap();
+            assert_eq!(decoded, msg);
+        }
+    }
+
+    #[test]
+    fn encode_decode_golay_as_bch_n23_k12_t2_test() {
+        let msg = bitvec![0, 1, 1, 0, 1, 0, 0, 0, 1, 1, 0, 1];
+        let prime_poly = bitvec![1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1];
+        let code = BchCode::new_non_primitive(23, 12, 2, &prime_poly);
+        assert_eq!(code.gen_poly().len(), 12);
+
+        let encoder = Encoder::from_code(&code);
+        let mut encoded = encoder.encode(&msg).unwrap();
+        encoded.inverse_nth(3);
+        encoded.inverse_nth(17);
+
+        let decoder = BerlekampDecoder::from_code(&code);
+        let (decoded, _) = decoder.decode(&encoded).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn encode_decode_even_weight_subcode_first_root_0_n15_k6_t2_test() {
+        let msg = bitvec![1, 1, 0, 1, 0, 1];
+        let code = BchCode::new(15, 6, 2, &bitvec![1, 0, 0, 1, 1]).with_first_root(0);
+
+        let encoder = Encoder::from_code(&code);
+        let encoded = encoder.encode(&msg).unwrap();
+        assert_eq!(encoded.count_ones() % 2, 0);
+
+        let decoder = BerlekampDecoder::from_code(&code);
+        for places in (0..15).combinations(2) {
+            let mut received = encoded.clone();
+            places.iter().for_each(|place| received.inverse_nth(*place));
+            let (decoded, _) = decoder.decode(&received).unwrap();
+            assert_eq!(decoded, msg);
+        }
+
+        let mut received = encoded.clone();
+        received.inverse_nth(14);
+        let decoder = SimpleDecoder::from_code(&code);
+        let (decoded, _) = decoder.decode(&received).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn encode_decode_first_root_3_n31_k11_t3_test() {
+        let msg = bitvec![1, 0, 1, 1, 0, 0, 1, 0, 1, 1, 1];
+        let code = BchCode::new(31, 11, 3, &bitvec![1, 0, 0, 1, 0, 1]).with_first_root(3);
+        assert_eq!(code.gen_poly().len(), 21);
+
+        let encoder = Encoder::from_code(&code);
+        let encoded = encoder.encode(&msg).unwrap();
+
+        let decoder = BerlekampDecoder::from_code(&code);
+        for places in (0..31).combinations(3) {
+            let mut received = encoded.clone();
+            places.iter().for_each(|place| received.inverse_nth(*place));
+            let (decoded, _) = decoder.decode(&received).unwrap();
+            assert_eq!(decoded, msg);
+        }
+    }
+
+    fn is_even_product(a: &BitVec, b: &BitVec) -> bool {
+        a.iter()
+            .by_vals()
+            .zip(b.iter().by_vals())
+            .filter(|(a, b)| *a && *b)
+            .count()
+            % 2
+            == 0
+    }
+
+    fn assert_generator_orthogonal_to_parity_check(code: &BchCode) {
+        let (gen_poly, field) = code.gen_poly_and_field();
+        let generator = generator_matrix(code.n, code.k, &gen_poly);
+        let parity_check = parity_check_matrix(code.n, code.k, &gen_poly);
+        let field_check = field_parity_check_matrix(code);
+        assert_eq!(generator.len(), code.k as usize);
+        assert_eq!(parity_check.len(), (code.n - code.k) as usize);
+
+        // every row of G is a code word: it has the 2t roots of the code
+        for g_row in generator.iter() {
+            for row in field_check.iter() {
+                let syndrome = row
+                    .iter()
+                    .zip(g_row.iter().by_vals())
+                    .filter(|(_, bit)| *bit)
+                    .fold(-1, |sum, (alpha, _)| field.add_alphas(sum, *alpha));
+                assert_eq!(syndrome, -1, "G row is not a code word of {:?}", code);
+            }
+        }
+
+        // x^j g(x) are code words, all cyclic shifts of g(x) for a code that
+        // is not shortened
+        let mut word = bitvec![0; code.n as usize - gen_poly.len()];
+        word.extend_from_bitslice(&gen_poly);
+        let shifts = if code.n == code.cycle_len {
+            code.n
+        } else {
+            code.k
+        };
+        for _ in 0..shifts {
+            for h_row in parity_check.iter() {
+                assert!(is_even_product(&word, h_row), "H * g^T != 0 for {:?}", code);
+            }
+            word.shift_cyclic(1);
+        }
+    }
+
+    #[test]
+    fn generator_times_parity_check_is_zero_test() {
+        let codes = vec![
+            BchCode::new(7, 4, 1, &bitvec![1, 0, 1, 1]),
+            BchCode::new(15, 7, 2, &bitvec![1, 0, 0, 1, 1]),
+            BchCode::new(31, 21, 2, &bitvec![1, 0, 0, 1, 0, 1]),
+            BchCode::new(25, 10, 3, &bitvec![1, 0, 0, 1, 0, 1]),
+            BchCode::new(31, 16, 3, &bitvec![1, 0, 0, 1, 0, 1]),
+            BchCode::new_non_primitive(21, 12, 2, &bitvec![1, 0, 0, 0, 0, 1, 1]),
+            BchCode::new_non_primitive(23, 12, 2, &bitvec![1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1]),
+            BchCode::new(15, 6, 2, &bitvec![1, 0, 0, 1, 1]).with_first_root(0),
+            BchCode::new(31, 11, 3, &bitvec![1, 0, 0, 1, 0, 1]).with_first_root(3),
+        ];
+        codes
+            .iter()
+            .for_each(assert_generator_orthogonal_to_parity_check);
+    }
+
+    #[test]
+    fn generator_times_parity_check_is_zero_long_codes_test() {
+        let codes = vec![
+            BchCode::new(200, 132, 9, &bitvec![1, 0, 1, 1, 0, 0, 0, 1, 1]),
+            BchCode::new(255, 191, 8, &bitvec![1, 0, 1, 1, 0, 1, 0, 0, 1]),
+            BchCode::new(255, 187, 9, &bitvec![1, 0, 1, 1, 0, 0, 0, 1, 1]),
+            BchCode::new(255, 191, 8, &bitvec![1, 0, 1, 1, 0, 0, 0, 1, 1]),
+            BchCode::new(511, 340, 20, &bitvec![1, 1, 0, 1, 1, 0, 0, 0, 0, 1]),
+        ];
+        codes
+            .iter()
+            .for_each(assert_generator_orthogonal_to_parity_check);
+    }
+
     #[test]
     #[ignore]
     fn encode_decode_without_errors_n255_k191_t8_test_full_layers() {
@@ -258,7 +485,6 @@ mod tests {
     }
 
     #[test]
-    #[ignore]   //fails
     fn decode_with_3_errors_n31_k16_t3_times_100() {
         let n = 31;
         let k = 16;
@@ -275,11 +501,11 @@ mod tests {
 
         let decoder = BerlekampDecoder::new(n, k, t, &prime_poly);
         
-        //fail plac [28, 25, 30]
-        for i in 0..1 {
+        let mut rng = test_rng();
+        for i in 0..100 {
             let mut encoded = msg.clone();
-            // let places = get_random_places(3, 31);
-            let places = vec![28, 25, 30];
+            // first the places that once failed
+            let places = if i == 0 { vec![28, 25, 30] } else { get_random_places(3, 31, &mut rng) };
             println!("plac {:?}", places);
             places.iter().for_each(|place| encoded.inverse_nth(*place));
             let (decoded, remainder) = decoder.decode(&encoded).unwrap(); //TODO remainder can be removed
@@ -0,0 +1,210 @@
+use crate::bch_code::BchCode;
+use crate::matrices;
+use bitvec::prelude::*;
+
+/// Largest dimension whose codewords are enumerated one by one.
+pub const MAX_ENUMERATION_DIMENSION: usize = 28;
+
+/// Number of codewords A_w of every weight w = 0..=n.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightDistribution {
+    pub counts: Vec<u64>,
+}
+
+impl WeightDistribution {
+    pub fn n(&self) -> i32 {
+        self.counts.len() as i32 - 1
+    }
+
+    /// True minimum distance, None for the zero code.
+    pub fn min_distance(&self) -> Option<i32> {
+        self.counts
+            .iter()
+            .skip(1)
+            .position(|count| *count != 0)
+            .map(|w| w as i32 + 1)
+    }
+
+    /// Probability that a BSC with crossover p turns a codeword into another
+    /// codeword, sum of A_w p^w (1-p)^(n-w) for w > 0.
+    pub fn undetected_error_probability(&self, p: f64) -> f64 {
+        let n = self.n();
+        self.counts
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(w, count)| *count as f64 * p.powi(w as i32) * (1.0 - p).powi(n - w as i32))
+            .sum()
+    }
+}
+
+/// Weight distribution of the code. Codewords are enumerated when k is small,
+/// otherwise the dual, generated by the parity-check matrix, is enumerated
+/// and transformed with the MacWilliams identity.
+pub fn weight_distribution(code: &BchCode) -> Result<WeightDistribution, String> {
+    let gen_poly = code.gen_poly();
+    if code.k <= code.n - code.k {
+        if code.k as usize > MAX_ENUMERATION_DIMENSION {
+            return Err("Code is too large for weight enumeration!".to_owned());
+        }
+        let generator = matrices::generator_matrix(code.n, code.k, &gen_poly);
+        Ok(enumerate_codewords(code.n, &generator))
+    } else {
+        if (code.n - code.k) as usize > MAX_ENUMERATION_DIMENSION {
+            return Err("Code is too large for weight enumeration!".to_owned());
+        }
+        let parity_check = matrices::parity_check_matrix(code.n, code.k, &gen_poly);
+        let dual = enumerate_codewords(code.n, &parity_check);
+        mac_williams(&dual, code.n - code.k)
+    }
+}
+
+/// Weights of all 2^k combinations of the rows, visited in Gray code order
+/// so every step adds a single row.
+pub fn enumerate_codewords(n: i32, generator: &[BitVec]) -> WeightDistribution {
+    let packed_rows: Vec<Vec<u64>> = generator.iter().map(pack_bits).collect();
+    let mut counts = vec![0u64; n as usize + 1];
+    let mut codeword = vec![0u64; (n as usize).div_ceil(64)];
+    counts[0] = 1;
+
+    for i in 1..(1u64 << generator.len()) {
+        let row = &packed_rows[i.trailing_zeros() as usize];
+        for (word, row_word) in codeword.iter_mut().zip(row.iter()) {
+            *word ^= row_word;
+        }
+        let weight: u32 = codeword.iter().map(|word| word.count_ones()).sum();
+        counts[weight as usize] += 1;
+    }
+    WeightDistribution { counts: counts }
+}
+
+/// MacWilliams identity: A_w = 2^-dual_k * sum of B_j K_w(j) over the weights
+/// B_j of the dual code of dimension dual_k, K_w being Krawtchouk polynomials.
+pub fn mac_williams(dual: &WeightDistribution, dual_k: i32) -> Result<WeightDistribution, String> {
+    let overflow = || "Weight enumeration overflow!".to_owned();
+    let n = dual.n() as usize;
+    let binomials = binomial_table(n).ok_or_else(overflow)?;
+
+    let mut counts = Vec::with_capacity(n + 1);
+    for w in 0..(n + 1) {
+        let mut sum: i128 = 0;
+        for (j, b_j) in dual.counts.iter().enumerate().filter(|(_, b_j)| **b_j != 0) {
+            let krawtchouk = krawtchouk(n, w, j, &binomials).ok_or_else(overflow)?;
+            let term = krawtchouk.checked_mul(*b_j as i128).ok_or_else(overflow)?;
+            sum = sum.checked_add(term).ok_or_else(overflow)?;
+        }
+        let count = sum >> dual_k;
+        if count < 0 || count << dual_k != sum {
+            return Err("Not a weight distribution of a linear code!".to_owned());
+        }
+        counts.push(count as u64);
+    }
+    Ok(WeightDistribution { counts: counts })
+}
+
+fn krawtchouk(n: usize, w: usize, j: usize, binomials: &[Vec<i128>]) -> Option<i128> {
+    (0..(w.min(j) + 1))
+        .filter(|i| w - i <= n - j)
+        .try_fold(0i128, |sum, i| {
+            let term = binomials[j][i].checked_mul(binomials[n - j][w - i])?;
+            if i % 2 == 0 {
+                sum.checked_add(term)
+            } else {
+                sum.checked_sub(term)
+            }
+        })
+}
+
+fn binomial_table(n: usize) -> Option<Vec<Vec<i128>>> {
+    let mut table: Vec<Vec<i128>> = vec![vec![1]];
+    for i in 1..(n + 1) {
+        let mut row = vec![1; i + 1];
+        for j in 1..i {
+            row[j] = table[i - 1][j - 1].checked_add(table[i - 1][j])?;
+        }
+        table.push(row);
+    }
+    Some(table)
+}
+
+fn pack_bits(bits: &BitVec) -> Vec<u64> {
+    let mut words = vec![0u64; bits.len().div_ceil(64)];
+    for (i, bit) in bits.iter().by_vals().enumerate() {
+        if bit {
+            words[i / 64] |= 1 << (i % 64);
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hamming_code_weight_distribution_test() {
+        let code = BchCode::new(7, 4, 1, &bitvec![1, 0, 1, 1]);
+        let result = weight_distribution(&code).unwrap();
+        assert_eq!(result.counts, vec![1, 0, 0, 7, 7, 0, 0, 1]);
+        assert_eq!(result.min_distance(), Some(3));
+    }
+
+    #[test]
+    fn enumeration_and_mac_williams_agree_test() {
+        let code = BchCode::new(15, 7, 2, &bitvec![1, 0, 0, 1, 1]);
+        let gen_poly = code.gen_poly();
+        let generator = matrices::generator_matrix(15, 7, &gen_poly);
+        let parity_check = matrices::parity_check_matrix(15, 7, &gen_poly);
+
+        let direct = enumerate_codewords(15, &generator);
+        let expected = vec![1, 0, 0, 0, 0, 18, 30, 15, 15, 30, 18, 0, 0, 0, 0, 1];
+        assert_eq!(direct.counts, expected);
+
+        let dual = enumerate_codewords(15, &parity_check);
+        assert_eq!(mac_williams(&dual, 8).unwrap(), direct);
+        assert_eq!(mac_williams(&direct, 7).unwrap(), dual);
+    }
+
+    #[test]
+    fn true_distance_exceeds_designed_distance_test() {
+        // designed distance 13, the code coincides with the t = 7 one
+        let code = BchCode::new(31, 6, 6, &bitvec![1, 0, 0, 1, 0, 1]);
+        let result = weight_distribution(&code).unwrap();
+        assert_eq!(result.min_distance(), Some(15));
+        assert_eq!(result.counts.iter().sum::<u64>(), 64);
+    }
+
+    #[test]
+    fn shortened_code_weight_distribution_test() {
+        let code = BchCode::new(25, 10, 3, &bitvec![1, 0, 0, 1, 0, 1]);
+        let result = weight_distribution(&code).unwrap();
+        assert_eq!(result.counts.iter().sum::<u64>(), 1 << 10);
+        assert!(result.min_distance().unwrap() >= 7);
+
+        let code = BchCode::new(25, 15, 2, &bitvec![1, 0, 0, 1, 0, 1]);
+        let result = weight_distribution(&code).unwrap();
+        assert_eq!(result.counts.iter().sum::<u64>(), 1 << 15);
+        assert!(result.min_distance().unwrap() >= 5);
+    }
+
+    #[test]
+    fn undetected_error_probability_test() {
+        let distribution = WeightDistribution {
+            counts: vec![1, 0, 0, 7, 7, 0, 0, 1],
+        };
+        let result = distribution.undetected_error_probability(0.5);
+        assert!((result - 15.0 / 128.0).abs() < 1e-12);
+
+        let p: f64 = 0.01;
+        let expected =
+            7.0 * p.powi(3) * (1.0 - p).powi(4) + 7.0 * p.powi(4) * (1.0 - p).powi(3) + p.powi(7);
+        let result = distribution.undetected_error_probability(p);
+        assert!((result - expected).abs() < 1e-15);
+    }
+
+    #[test]
+    fn too_large_code_test() {
+        let code = BchCode::new(255, 191, 8, &bitvec![1, 0, 1, 1, 0, 1, 0, 0, 1]);
+        assert!(weight_distribution(&code).is_err());
+    }
+}
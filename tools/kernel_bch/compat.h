@@ -0,0 +1,91 @@
+/*
+ * Just enough of the kernel headers to build lib/bch.c in user space. The
+ * Makefile points every header included by bch.c here, which also shadows
+ * the system <linux/...> headers: only C library headers that do not reach
+ * them are included.
+ */
+#ifndef KERNEL_BCH_COMPAT_H
+#define KERNEL_BCH_COMPAT_H
+
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+typedef uint8_t u8;
+typedef uint16_t u16;
+typedef uint32_t u32;
+typedef uint64_t u64;
+typedef int32_t s32;
+typedef uint32_t __be32;
+
+#define ENOMEM 12
+#define EINVAL 22
+#define EBADMSG 74
+
+#define GFP_KERNEL 0
+#define kmalloc(size, flags) malloc(size)
+#define kzalloc(size, flags) calloc(1, size)
+#define kcalloc(n, size, flags) calloc(n, size)
+#define kmalloc_array(n, size, flags) calloc(n, size)
+#define kfree(ptr) free(ptr)
+
+#define __init
+#define __exit
+#define __maybe_unused __attribute__((unused))
+#define EXPORT_SYMBOL(sym)
+#define EXPORT_SYMBOL_GPL(sym)
+#define MODULE_LICENSE(license)
+#define MODULE_AUTHOR(author)
+#define MODULE_DESCRIPTION(description)
+#define module_init(fn)
+#define module_exit(fn)
+
+#define likely(x) __builtin_expect(!!(x), 1)
+#define unlikely(x) __builtin_expect(!!(x), 0)
+#define BUG_ON(x) do { if (x) abort(); } while (0)
+#define WARN_ON(x) (!!(x))
+#define BUILD_BUG_ON(x) _Static_assert(!(x), #x)
+#define ARRAY_SIZE(a) (sizeof(a) / sizeof((a)[0]))
+#define DIV_ROUND_UP(n, d) (((n) + (d) - 1) / (d))
+#define BITS_PER_BYTE 8
+#define min(a, b) ((a) < (b) ? (a) : (b))
+#define max(a, b) ((a) > (b) ? (a) : (b))
+
+#define KERN_ERR ""
+#define printk(...) fprintf(stderr, __VA_ARGS__)
+#define pr_err(...) fprintf(stderr, __VA_ARGS__)
+#define pr_warn(...) fprintf(stderr, __VA_ARGS__)
+#define pr_debug(...) do { } while (0)
+
+#if __BYTE_ORDER__ == __ORDER_LITTLE_ENDIAN__
+#define cpu_to_be32(x) __builtin_bswap32(x)
+#else
+#define cpu_to_be32(x) ((u32)(x))
+#endif
+#define be32_to_cpu(x) cpu_to_be32(x)
+
+static inline int fls(unsigned int x)
+{
+	return x ? 32 - __builtin_clz(x) : 0;
+}
+
+static inline u8 bitrev8(u8 x)
+{
+	x = (u8)((x >> 4) | (x << 4));
+	x = (u8)(((x & 0xcc) >> 2) | ((x & 0x33) << 2));
+	return (u8)(((x & 0xaa) >> 1) | ((x & 0x55) << 1));
+}
+
+static inline u16 bitrev16(u16 x)
+{
+	return (u16)((bitrev8(x & 0xff) << 8) | bitrev8(x >> 8));
+}
+
+static inline u32 bitrev32(u32 x)
+{
+	return ((u32)bitrev16(x & 0xffff) << 16) | bitrev16(x >> 16);
+}
+
+#endif
@@ -0,0 +1,143 @@
+/*
+ * Recomputes testdata/kernel_bch_vectors.txt with lib/bch. Reads the
+ * vectors on stdin and writes them back with every ECC, received word and
+ * error location computed by bch_encode() and bch_decode(), so that the
+ * output equals the input exactly when the kernel agrees with the file.
+ * The error locations of a decode line only choose the bits to flip.
+ */
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <linux/bch.h>
+
+static size_t from_hex(const char *hex, uint8_t **out)
+{
+	size_t len = strlen(hex) / 2;
+	size_t i;
+
+	*out = calloc(len ? len : 1, 1);
+	for (i = 0; i < len; i++) {
+		unsigned int byte;
+
+		sscanf(hex + 2 * i, "%2x", &byte);
+		(*out)[i] = (uint8_t)byte;
+	}
+	return len;
+}
+
+static void print_hex(const uint8_t *buf, size_t len)
+{
+	size_t i;
+
+	putchar(' ');
+	for (i = 0; i < len; i++)
+		printf("%02x", buf[i]);
+}
+
+static int compare(const void *a, const void *b)
+{
+	unsigned int x = *(const unsigned int *)a;
+	unsigned int y = *(const unsigned int *)b;
+
+	return (x > y) - (x < y);
+}
+
+static void encode(struct bch_control *bch, const uint8_t *data, size_t len,
+		   uint8_t *ecc)
+{
+	memset(ecc, 0, bch->ecc_bytes);
+	bch_encode(bch, data, len, ecc);
+}
+
+/* decode <data> <received data> <received ecc> <calculated ecc> <errloc,...> */
+static void decode(struct bch_control *bch, char **fields)
+{
+	uint8_t *data, *word, *calc_ecc;
+	unsigned int *errloc = calloc(bch->t, sizeof(*errloc));
+	size_t len = from_hex(fields[0], &data);
+	char *location;
+	int count, i;
+
+	word = calloc(len + bch->ecc_bytes, 1);
+	calc_ecc = calloc(bch->ecc_bytes, 1);
+	memcpy(word, data, len);
+	encode(bch, data, len, word + len);
+	for (location = strtok(fields[4], ","); location;
+	     location = strtok(NULL, ",")) {
+		unsigned long l = strtoul(location, NULL, 10);
+
+		word[l / 8] ^= 1 << (l % 8);
+	}
+	encode(bch, word, len, calc_ecc);
+	count = bch_decode(bch, NULL, len, word + len, calc_ecc, NULL, errloc);
+
+	print_hex(data, len);
+	print_hex(word, len);
+	print_hex(word + len, bch->ecc_bytes);
+	print_hex(calc_ecc, bch->ecc_bytes);
+	if (count < 0) {
+		printf(" error%d\n", count);
+	} else {
+		qsort(errloc, count, sizeof(*errloc), compare);
+		for (i = 0; i < count; i++)
+			printf("%c%u", i ? ',' : ' ', errloc[i]);
+		putchar('\n');
+	}
+	free(data);
+	free(word);
+	free(calc_ecc);
+	free(errloc);
+}
+
+int main(void)
+{
+	char *line = NULL;
+	size_t size = 0;
+
+	while (getline(&line, &size, stdin) != -1) {
+		char *fields[10];
+		int count = 0;
+		unsigned int m, t, prim_poly, swap_bits;
+		struct bch_control *bch;
+
+		if (line[0] == '#' || line[0] == '\n') {
+			fputs(line, stdout);
+			continue;
+		}
+		for (fields[0] = strtok(line, " \n"); fields[count] && count < 9;
+		     fields[++count] = strtok(NULL, " \n"))
+			;
+		if (count < 7) {
+			fprintf(stderr, "Malformed line: %s\n", fields[0]);
+			return 1;
+		}
+		m = strtoul(fields[1], NULL, 10);
+		t = strtoul(fields[2], NULL, 10);
+		prim_poly = strtoul(fields[3], NULL, 16);
+		swap_bits = strtoul(fields[4], NULL, 10);
+		bch = bch_init(m, t, prim_poly, swap_bits);
+		if (!bch) {
+			fprintf(stderr, "bch_init(%u, %u, %#x, %u) failed\n",
+				m, t, prim_poly, swap_bits);
+			return 1;
+		}
+
+		printf("%s %u %u %#x %u", fields[0], m, t, prim_poly, swap_bits);
+		if (strcmp(fields[0], "encode") == 0) {
+			uint8_t *data, *ecc = calloc(bch->ecc_bytes, 1);
+			size_t len = from_hex(fields[5], &data);
+
+			encode(bch, data, len, ecc);
+			print_hex(data, len);
+			print_hex(ecc, bch->ecc_bytes);
+			putchar('\n');
+			free(data);
+			free(ecc);
+		} else {
+			decode(bch, fields + 5);
+		}
+		bch_free(bch);
+	}
+	free(line);
+	return 0;
+}
@@ -0,0 +1,165 @@
+#!/usr/bin/env python3
+"""Model of Linux lib/bch writing testdata/kernel_bch_vectors.txt.
+
+The model follows the kernel conventions as read from the lib/bch sources: generator polynomial from the
+minimal polynomials of alpha^1..alpha^2t, data bits most significant first,
+ECC bits left aligned in DIV_ROUND_UP(m * t, 8) bytes, every byte bit
+reversed with swap_bits, and decode_bch() error location l standing for
+bit l % 8 (least significant first) of byte l / 8 of data followed by ECC.
+
+The output has not been checked against the kernel yet, harness.c and
+`make check` of the Makefile do that given a kernel source tree. Usage: reference.py > testdata/kernel_bch_vectors.txt
+"""
+
+import random
+
+# default primitive polynomials of lib/bch, m = 5..15
+PRIM = {5: 0x25, 6: 0x43, 7: 0x83, 8: 0x11d, 9: 0x211, 10: 0x409,
+        11: 0x805, 12: 0x1053, 13: 0x201b, 14: 0x402b, 15: 0x8003}
+
+# m, t, swap_bits, data bytes
+CASES = [
+    (5, 2, 0, 2),
+    (6, 4, 0, 4),
+    # deg g = 45 < m * t = 54, the last ECC byte is padding only
+    (6, 9, 0, 2),
+    (8, 4, 0, 16),
+    (8, 4, 1, 16),
+    (10, 3, 0, 64),
+    (13, 4, 0, 512),
+    (13, 4, 1, 512),
+    (13, 8, 0, 512),
+    (14, 8, 0, 1024),
+    (15, 2, 0, 256),
+]
+
+SEED = 49
+
+
+def gf_tables(m, prim):
+    n = (1 << m) - 1
+    exp = [0] * (2 * n)
+    log = [0] * (n + 1)
+    x = 1
+    for i in range(n):
+        exp[i] = x
+        log[x] = i
+        x <<= 1
+        if x >> m:
+            x ^= prim
+    for i in range(n, 2 * n):
+        exp[i] = exp[i - n]
+    return exp, log
+
+
+def genpoly(m, t, prim):
+    """Generator polynomial, bit i being the coefficient of x^i."""
+    n = (1 << m) - 1
+    exp, log = gf_tables(m, prim)
+
+    def mul(a, b):
+        return 0 if a == 0 or b == 0 else exp[log[a] + log[b]]
+
+    done = set()
+    g = [1]
+    for i in range(1, 2 * t + 1):
+        if i in done:
+            continue
+        coset = []
+        j = i
+        while j not in coset:
+            coset.append(j)
+            j = j * 2 % n
+        done.update(coset)
+        # minimal polynomial over GF(2^m), lowest degree first
+        mp = [1]
+        for r in coset:
+            new = [0] * (len(mp) + 1)
+            for d, c in enumerate(mp):
+                new[d + 1] ^= c
+                new[d] ^= mul(c, exp[r])
+            mp = new
+        assert all(c in (0, 1) for c in mp)
+        res = [0] * (len(g) + len(mp) - 1)
+        for a, ca in enumerate(g):
+            for b, cb in enumerate(mp):
+                res[a + b] ^= ca & cb
+        g = res
+    return sum(c << d for d, c in enumerate(g))
+
+
+def bits_of(data, swap):
+    return [(b >> (i if swap else 7 - i)) & 1 for b in data for i in range(8)]
+
+
+def pack(bits, length, swap):
+    out = bytearray(length)
+    for i, x in enumerate(bits):
+        if x:
+            out[i // 8] |= 1 << (i % 8 if swap else 7 - i % 8)
+    return bytes(out)
+
+
+def encode(m, t, g, swap, data):
+    deg = g.bit_length() - 1
+    r = 0
+    for x in bits_of(data, swap):
+        fb = ((r >> (deg - 1)) & 1) ^ x
+        r = (r << 1) & ((1 << deg) - 1)
+        if fb:
+            r ^= g & ((1 << deg) - 1)
+    ecc = [(r >> (deg - 1 - i)) & 1 for i in range(deg)]
+    return pack(ecc, (m * t + 7) // 8, swap)
+
+
+def errloc(p, swap):
+    """decode_bch() location of bit p of the data and ECC bit stream."""
+    return p if swap else (p & ~7) | (7 - (p & 7))
+
+
+def flip(buf, location):
+    buf[location // 8] ^= 1 << (location % 8)
+
+
+def main():
+    rng = random.Random(SEED)
+    print("# Linux lib/bch vectors: ECC of bch_encode() on a zeroed ECC buffer and")
+    print("# error locations of bch_decode() given received and calculated ECC, in")
+    print("# increasing order.")
+    print("#")
+    print("# Written by tools/kernel_bch/reference.py, a model independent of this")
+    print("# crate. tools/kernel_bch/harness.c recomputes every line with lib/bch")
+    print("# of a kernel source tree, `make -C tools/kernel_bch check` compares.")
+    print("#")
+    print("# encode <m> <t> <prim_poly> <swap_bits> <data> <ecc>")
+    print("# decode <m> <t> <prim_poly> <swap_bits> <data> <received data> "
+          "<received ecc> <calculated ecc> <errloc,...>")
+    for m, t, swap, length in CASES:
+        prim = PRIM[m]
+        g = genpoly(m, t, prim)
+        deg = g.bit_length() - 1
+        assert 8 * length <= (1 << m) - 1 - deg
+        params = "%d %d %#x %d" % (m, t, prim, swap)
+
+        data = bytes(rng.randrange(256) for _ in range(length))
+        ecc = encode(m, t, g, swap, data)
+        print("encode %s %s %s" % (params, data.hex(), ecc.hex()))
+        if length >= 16:
+            ones = b"\xff" * length
+            print("encode %s %s %s" % (params, ones.hex(), encode(m, t, g, swap, ones).hex()))
+
+        for count in (1, t):
+            positions = rng.sample(range(8 * length + deg), count)
+            locations = sorted(errloc(p, swap) for p in positions)
+            word = bytearray(data + ecc)
+            for location in locations:
+                flip(word, location)
+            received, recv_ecc = bytes(word[:length]), bytes(word[length:])
+            calc_ecc = encode(m, t, g, swap, received)
+            print("decode %s %s %s %s %s %s" % (
+                params, data.hex(), received.hex(), recv_ecc.hex(), calc_ecc.hex(),
+                ",".join(str(location) for location in locations)))
+
+
+if __name__ == "__main__":
+    main()